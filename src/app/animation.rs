@@ -0,0 +1,194 @@
+/// What happens when an animation reaches its end.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlaybackMode {
+    /// Stops at the end.
+    Once,
+    /// Jumps back to the start.
+    Loop,
+    /// Turns around and plays backwards, then forwards again.
+    PingPong,
+}
+
+/// Playback state of the larp ratio animation. The progress is the linear fraction of the duration
+/// that has passed, easing is applied on top of it.
+#[derive(Debug, Clone, Copy)]
+pub struct Animation {
+    playing: bool,
+    progress: f32,
+    forward: bool,
+}
+
+impl Animation {
+    pub fn new() -> Self {
+        Self { playing: false, progress: 0.0, forward: true }
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    pub fn progress(&self) -> f32 {
+        self.progress
+    }
+
+    /// Starts or resumes playback. A finished one-off animation starts over.
+    pub fn play(&mut self, mode: PlaybackMode) {
+        let is_finished = if self.forward { self.progress >= 1.0 } else { self.progress <= 0.0 };
+        if mode == PlaybackMode::Once && is_finished {
+            self.progress = 0.0;
+            self.forward = true;
+        }
+        self.playing = true;
+    }
+
+    pub fn pause(&mut self) {
+        self.playing = false;
+    }
+
+    /// Pauses and goes back to the start.
+    pub fn stop(&mut self) {
+        self.playing = false;
+        self.progress = 0.0;
+        self.forward = true;
+    }
+
+    /// Moves the progress to `progress`, e.g. when the animated value is edited by hand.
+    pub fn seek(&mut self, progress: f32) {
+        self.progress = progress.clamp(0.0, 1.0);
+    }
+
+    /// Advances the animation by `delta_s` seconds and returns the new progress while it plays.
+    pub fn advance(&mut self, delta_s: f32, duration_s: f32, mode: PlaybackMode) -> Option<f32> {
+        if !self.playing {
+            return None;
+        }
+
+        let step = delta_s / duration_s.max(f32::EPSILON);
+        let mut progress = if self.forward { self.progress + step } else { self.progress - step };
+        match mode {
+            PlaybackMode::Once if progress >= 1.0 || progress <= 0.0 => {
+                progress = progress.clamp(0.0, 1.0);
+                self.playing = false;
+            },
+            PlaybackMode::Once => (),
+            PlaybackMode::Loop => progress = progress.rem_euclid(1.0),
+            PlaybackMode::PingPong => {
+                // Reflect at both ends, a long frame may even cross a whole period.
+                let period_position = progress.rem_euclid(2.0);
+                let (reflected, is_reversed) = if period_position > 1.0 { (2.0 - period_position, true) } else { (period_position, false) };
+                if is_reversed {
+                    self.forward = !self.forward;
+                }
+                progress = reflected;
+            },
+        }
+        self.progress = progress;
+        Some(progress)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DURATION: f32 = 2.0;
+
+    fn playing(mode: PlaybackMode, progress: f32) -> Animation {
+        let mut animation = Animation::new();
+        animation.seek(progress);
+        animation.play(mode);
+        animation
+    }
+
+    fn assert_close(actual: Option<f32>, expected: f32) {
+        let actual = actual.expect("animation should be playing");
+        assert!((actual - expected).abs() < 1e-5, "expected {}, got {}", expected, actual);
+    }
+
+    #[test]
+    fn once_stops_at_the_end() {
+        let mut animation = playing(PlaybackMode::Once, 0.0);
+        assert_close(animation.advance(0.5, DURATION, PlaybackMode::Once), 0.25);
+        assert_close(animation.advance(1.5, DURATION, PlaybackMode::Once), 1.0);
+        assert!(!animation.is_playing());
+        assert_eq!(animation.advance(0.5, DURATION, PlaybackMode::Once), None);
+
+        animation.play(PlaybackMode::Once);
+        assert_close(animation.advance(0.5, DURATION, PlaybackMode::Once), 0.25);
+    }
+
+    #[test]
+    fn loop_wraps_around() {
+        let mut animation = playing(PlaybackMode::Loop, 0.75);
+        assert_close(animation.advance(1.0, DURATION, PlaybackMode::Loop), 0.25);
+        assert_close(animation.advance(DURATION, DURATION, PlaybackMode::Loop), 0.25);
+        assert!(animation.is_playing());
+    }
+
+    #[test]
+    fn ping_pong_reflects_at_both_ends() {
+        let mut animation = playing(PlaybackMode::PingPong, 0.75);
+        assert_close(animation.advance(1.0, DURATION, PlaybackMode::PingPong), 0.75);
+        assert_close(animation.advance(0.5, DURATION, PlaybackMode::PingPong), 0.5);
+        assert_close(animation.advance(1.5, DURATION, PlaybackMode::PingPong), 0.25);
+        assert_close(animation.advance(0.5, DURATION, PlaybackMode::PingPong), 0.5);
+    }
+
+    #[test]
+    fn large_delta_crosses_whole_periods() {
+        let mut animation = playing(PlaybackMode::PingPong, 0.25);
+        // Up to 1, down to 0, up to 0.5: still moving forwards.
+        assert_close(animation.advance(2.5 * DURATION, DURATION, PlaybackMode::PingPong), 0.75);
+        assert_close(animation.advance(0.5, DURATION, PlaybackMode::PingPong), 1.0);
+
+        let mut animation = playing(PlaybackMode::Loop, 0.25);
+        assert_close(animation.advance(7.5 * DURATION, DURATION, PlaybackMode::Loop), 0.75);
+
+        let mut animation = playing(PlaybackMode::Once, 0.25);
+        assert_close(animation.advance(10.0 * DURATION, DURATION, PlaybackMode::Once), 1.0);
+        assert!(!animation.is_playing());
+    }
+
+    #[test]
+    fn exact_duration_reaches_the_end() {
+        let mut animation = playing(PlaybackMode::Once, 0.0);
+        assert_close(animation.advance(DURATION, DURATION, PlaybackMode::Once), 1.0);
+        assert!(!animation.is_playing());
+
+        let mut animation = playing(PlaybackMode::PingPong, 0.0);
+        assert_close(animation.advance(DURATION, DURATION, PlaybackMode::PingPong), 1.0);
+        assert_close(animation.advance(0.5, DURATION, PlaybackMode::PingPong), 0.75);
+    }
+
+    #[test]
+    fn reversed_direction_is_kept_across_modes() {
+        let mut animation = playing(PlaybackMode::PingPong, 0.75);
+        assert_close(animation.advance(1.0, DURATION, PlaybackMode::PingPong), 0.75);
+        // Still moving backwards when switched to the other modes.
+        assert_close(animation.advance(1.0, DURATION, PlaybackMode::Loop), 0.25);
+        assert_close(animation.advance(1.0, DURATION, PlaybackMode::Loop), 0.75);
+        assert_close(animation.advance(2.0, DURATION, PlaybackMode::Once), 0.0);
+        assert!(!animation.is_playing());
+
+        animation.play(PlaybackMode::Once);
+        assert_close(animation.advance(0.5, DURATION, PlaybackMode::Once), 0.25);
+    }
+
+    #[test]
+    fn seek_and_pause() {
+        let mut animation = playing(PlaybackMode::Loop, 0.0);
+        animation.pause();
+        assert_eq!(animation.advance(0.5, DURATION, PlaybackMode::Loop), None);
+        assert_eq!(animation.progress(), 0.0);
+
+        animation.seek(1.5);
+        assert_eq!(animation.progress(), 1.0);
+        animation.seek(0.5);
+        animation.play(PlaybackMode::Loop);
+        assert_close(animation.advance(0.5, DURATION, PlaybackMode::Loop), 0.75);
+
+        animation.stop();
+        assert!(!animation.is_playing());
+        assert_eq!(animation.progress(), 0.0);
+    }
+}
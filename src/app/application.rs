@@ -77,7 +77,10 @@ impl Application {
             }
 
             self.gui_layer.handle_user_input(window_proxy::Window::new(&mut self.window));
-            self.gui_layer.render(window_proxy::Window::new(&mut self.window));
+            self.gui_layer.render(window_proxy::Window::new(&mut self.window), &self.editor_layer);
+            for command in self.gui_layer.take_commands() {
+                self.editor_layer.handle_command(command, self.gui_layer.editor_config());
+            }
 
             self.editor_layer.render(&mut self.renderer, self.gui_layer.editor_config());
 
@@ -127,6 +130,12 @@ impl Application {
                     }
                 }
             },
+            WindowEvent::Key(key, _, action, modifiers) => match action {
+                glfw::Action::Release => Some(ApplicationEvent::KeyReleased { key }),
+                _ => Some(ApplicationEvent::KeyPressed { key, modifiers }),
+            },
+            WindowEvent::Char(character) => Some(ApplicationEvent::CharacterTyped { character }),
+            WindowEvent::Scroll(_, y_offset) => Some(ApplicationEvent::MouseScrolled { delta: y_offset as f32 }),
             WindowEvent::FramebufferSize(width, height) => {
                 self.renderer.set_viewport((width as u32, height as u32));
                 Some(ApplicationEvent::FramebufferResized { width: width as u32, height: height as u32 })
@@ -136,6 +145,7 @@ impl Application {
     }
 
     fn handle_event(&mut self, event: ApplicationEvent) {
-        self.editor_layer.handle_event(event, window_proxy::Window::new(&mut self.window));
+        self.gui_layer.handle_event(event);
+        self.editor_layer.handle_event(event, window_proxy::Window::new(&mut self.window), self.gui_layer.editor_config());
     }
 }
\ No newline at end of file
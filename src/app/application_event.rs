@@ -7,5 +7,9 @@ pub enum ApplicationEvent {
     MouseLeftButtonReleased,
     MouseRightButtonPressed,
     MouseRightButtonReleased,
+    MouseScrolled { delta: f32 },
     FramebufferResized { width: u32, height: u32},
+    KeyPressed { key: glfw::Key, modifiers: glfw::Modifiers },
+    KeyReleased { key: glfw::Key },
+    CharacterTyped { character: char },
 }
\ No newline at end of file
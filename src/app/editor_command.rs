@@ -0,0 +1,26 @@
+use bezier_curve_editor::curve::path::Continuity;
+use bezier_curve_editor::curve::bspline::KnotVectorKind;
+use bezier_curve_editor::curve::easing::CubicBezierEasing;
+
+#[derive(Clone, Debug)]
+pub enum EditorCommand {
+    SetControlPointWeight { index: usize, weight: f32 },
+    LoadQuarterCirclePreset,
+    SetJointContinuity { index: usize, continuity: Continuity },
+    SetKnot { index: usize, value: f32 },
+    ResetKnots { kind: KnotVectorKind },
+    InsertKnot { t: f32 },
+    ResetTangents,
+    ElevateDegree,
+    ReduceDegree,
+    SplitAt { t: f32 },
+    ToggleIntersectionLine,
+    /// Writes the stroke outline to `path`, replacing an existing file only when `overwrite` is set.
+    ExportStrokeOutline { path: String, overwrite: bool },
+    SetEasing { easing: CubicBezierEasing },
+    Undo,
+    Redo,
+    JumpToHistory { position: usize },
+    /// Starts a new edit session, e.g. when a slider starts being dragged, so that its edits become one undo step.
+    BeginEdit,
+}
@@ -1,23 +1,155 @@
 
-#[derive(Debug, Clone, Copy)]
+use bezier_curve_editor::curve::interpolating::CatmullRomParameterization;
+use bezier_curve_editor::curve::offset::{LineCap, LineJoin};
+use bezier_curve_editor::curve::easing::Easing;
+use crate::app::animation::PlaybackMode;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CurveMode {
+    Bezier,
+    Path,
+    BSpline,
+    Nurbs,
+    CatmullRom,
+    Hermite,
+    KochanekBartels,
+    Interpolated,
+    Easing,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct EditorConfig {
+    pub curve_mode: CurveMode,
+    pub path_segment_degree: i32,
+    pub bspline_degree: i32,
+    pub catmull_rom_parameterization: CatmullRomParameterization,
+    pub interpolation_parameterization: CatmullRomParameterization,
+    pub tension: f32,
+    pub continuity: f32,
+    pub bias: f32,
     pub larp_ratio: f32,
+    pub animation_duration: f32,
+    pub animation_easing: Easing,
+    pub playback_mode: PlaybackMode,
+    pub constant_speed: bool,
     pub samples: i32,
+    pub adaptive_flattening: bool,
+    pub bernstein_evaluation: bool,
+    pub flattening_tolerance: f32,
     pub bezier_curve_color: [f32; 3],
     pub control_points_color: [f32; 3],
     pub control_points_strip_color: [f32; 3],
     pub larp_point_color: [f32; 3],
+    pub show_de_casteljau: bool,
+    pub de_casteljau_first_color: [f32; 3],
+    pub de_casteljau_last_color: [f32; 3],
+    pub show_intersections: bool,
+    pub intersection_color: [f32; 3],
+    pub show_closest_point: bool,
+    pub show_geometry_checks: bool,
+    pub mark_curve_features: bool,
+    pub cusp_color: [f32; 3],
+    pub inflection_color: [f32; 3],
+    pub show_bounding_box: bool,
+    pub bounding_box_color: [f32; 3],
+    pub show_extrema: bool,
+    pub extrema_color: [f32; 3],
+    pub show_convex_hull: bool,
+    pub convex_hull_color: [f32; 3],
+    pub show_curvature_comb: bool,
+    pub curvature_comb_scale: f32,
+    pub curvature_comb_color: [f32; 3],
+    pub show_curvature_plot: bool,
+    pub curvature_plot_over_arc_length: bool,
+    pub show_hodograph: bool,
+    pub show_tangent: bool,
+    pub tangent_color: [f32; 3],
+    pub show_motion_preview: bool,
+    pub motion_sprite_size: f32,
+    pub motion_sprite_color: [f32; 3],
+    pub show_frenet_frame: bool,
+    pub normal_color: [f32; 3],
+    pub show_stroke: bool,
+    pub stroke_width: f32,
+    pub stroke_cap: LineCap,
+    pub stroke_join: LineJoin,
+    pub miter_limit: f32,
+    pub offset_tolerance: f32,
+    pub stroke_color: [f32; 3],
+    pub freehand: bool,
+    pub freehand_tolerance: f32,
+    pub freehand_replaces: bool,
+    pub show_interpolation_polygon: bool,
+    pub interpolation_polygon_color: [f32; 3],
+    pub easing_axes_color: [f32; 3],
 }
 
 impl EditorConfig {
     pub fn default() -> Self {
         Self {
+            curve_mode: CurveMode::Bezier,
+            path_segment_degree: 3,
+            bspline_degree: 3,
+            catmull_rom_parameterization: CatmullRomParameterization::Centripetal,
+            interpolation_parameterization: CatmullRomParameterization::Chordal,
+            tension: 0.0,
+            continuity: 0.0,
+            bias: 0.0,
             larp_ratio: 0.5,
+            animation_duration: 3.0,
+            animation_easing: Easing::EaseInOut,
+            playback_mode: PlaybackMode::PingPong,
+            constant_speed: false,
             samples: 100,
+            adaptive_flattening: false,
+            bernstein_evaluation: false,
+            flattening_tolerance: 0.25,
             bezier_curve_color: [0.1, 0.2, 0.9],
             control_points_color: [0.7, 0.7, 0.1],
             control_points_strip_color: [0.8, 0.2, 0.2],
             larp_point_color: [0.3, 0.9, 0.3],
+            show_de_casteljau: false,
+            de_casteljau_first_color: [0.9, 0.6, 0.2],
+            de_casteljau_last_color: [0.2, 0.7, 0.9],
+            show_intersections: false,
+            intersection_color: [0.9, 0.4, 0.9],
+            show_closest_point: false,
+            show_geometry_checks: false,
+            mark_curve_features: false,
+            cusp_color: [0.9, 0.1, 0.1],
+            inflection_color: [0.1, 0.8, 0.8],
+            show_bounding_box: false,
+            bounding_box_color: [0.5, 0.5, 0.5],
+            show_extrema: false,
+            extrema_color: [0.9, 0.6, 0.1],
+            show_convex_hull: false,
+            convex_hull_color: [0.3, 0.6, 0.3],
+            show_curvature_comb: false,
+            curvature_comb_scale: 2000.0,
+            curvature_comb_color: [0.6, 0.3, 0.8],
+            show_curvature_plot: false,
+            curvature_plot_over_arc_length: false,
+            show_hodograph: false,
+            show_tangent: false,
+            tangent_color: [0.9, 0.9, 0.9],
+            show_motion_preview: false,
+            motion_sprite_size: 24.0,
+            motion_sprite_color: [0.95, 0.8, 0.2],
+            show_frenet_frame: false,
+            normal_color: [0.9, 0.3, 0.6],
+            show_stroke: false,
+            stroke_width: 30.0,
+            stroke_cap: LineCap::Butt,
+            stroke_join: LineJoin::Miter,
+            miter_limit: 4.0,
+            offset_tolerance: 0.25,
+            stroke_color: [0.9, 0.5, 0.2],
+            freehand: false,
+            freehand_tolerance: 4.0,
+            freehand_replaces: true,
+            show_interpolation_polygon: true,
+            interpolation_polygon_color: [0.5, 0.5, 0.6],
+            easing_axes_color: [0.45, 0.45, 0.45],
         }
     }
 }
\ No newline at end of file
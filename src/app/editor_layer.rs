@@ -1,7 +1,186 @@
+use std::cell::{Ref, RefCell};
+use std::io::Write;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
 use crate::app::gl_renderer::Renderer;
 use crate::app::window_proxy::Window;
 use crate::app::application_event::ApplicationEvent;
-use crate::app::editor_config::EditorConfig;
+use crate::app::editor_config::{EditorConfig, CurveMode};
+use crate::app::editor_command::EditorCommand;
+use crate::app::history::History;
+use bezier_curve_editor::curve::bezier::{lerp, BezierCurve, Projection};
+use bezier_curve_editor::curve::path::{BezierPath, Continuity};
+use bezier_curve_editor::curve::bspline::{self, BSplineCurve, KnotVectorKind};
+use bezier_curve_editor::curve::nurbs::NurbsCurve;
+use bezier_curve_editor::curve::interpolating::{self, CatmullRomParameterization};
+use bezier_curve_editor::curve::arc_length::ArcLengthTable;
+use bezier_curve_editor::curve::flatten;
+use bezier_curve_editor::curve::intersection::{self, Intersection};
+use bezier_curve_editor::curve::analysis;
+use bezier_curve_editor::curve::offset::{self, StrokeStyle};
+use bezier_curve_editor::curve::svg;
+use bezier_curve_editor::curve::fitting;
+use bezier_curve_editor::curve::evaluator::{BernsteinTable, BezierEvaluator};
+use bezier_curve_editor::curve::easing::CubicBezierEasing;
+pub const MIN_CONTROL_POINT_WEIGHT: f32 = 0.01;
+pub const MAX_CONTROL_POINT_WEIGHT: f32 = 100.0;
+const WEIGHT_SCROLL_FACTOR: f32 = 1.1;
+const DEFAULT_TANGENT_LENGTH: f32 = 0.1;
+const DEVIATION_SAMPLES: usize = 200;
+const ARC_LENGTH_SAMPLES: usize = 512;
+
+const INTERSECTION_TOLERANCE: f32 = 0.05;
+const HOVER_DISTANCE: f32 = 30.0;
+const CURVATURE_SAMPLES: usize = 150;
+const HODOGRAPH_SAMPLES: usize = 100;
+/// Cosine of the largest angle between the handles of a joint that still counts as smooth.
+const SMOOTH_JOINT_COSINE: f32 = 0.999;
+/// Side of the easing unit square relative to the shorter side of the working area, the rest
+/// leaves room for handles that overshoot in y.
+const EASING_SQUARE_RATIO: f32 = 0.6;
+const EASING_TICKS: usize = 10;
+/// Wheel ticks closer together than this belong to the same weight edit.
+const SCROLL_EDIT_TIMEOUT: Duration = Duration::from_millis(500);
+const FRENET_ARROW_LENGTH: f32 = 50.0;
+const ARROW_HEAD_LENGTH: f32 = 8.0;
+
+type Evaluator = Box<dyn Fn(f32) -> Option<(f32, f32)>>;
+type Segments = Vec<(BezierCurve, (f32, f32))>;
+/// Axis-aligned box as its minimum and maximum corner.
+type BoundingBox = ((f32, f32), (f32, f32));
+/// Teeth of a curvature comb as pairs of curve point and tooth tip.
+type CombTeeth = Vec<((f32, f32), (f32, f32))>;
+
+/// Spots of the edited curve that usually indicate bad geometry, with their curve parameters and positions.
+#[derive(Debug, Clone, Default)]
+pub struct CurveFeatures {
+    pub self_intersections: Vec<Intersection>,
+    pub cusps: Vec<(f32, (f32, f32))>,
+    pub inflections: Vec<(f32, (f32, f32))>,
+}
+
+/// First and second derivative of the edited curve with respect to its parameter, in window pixels.
+#[derive(Debug, Clone, Default)]
+pub struct Hodograph {
+    /// Derivative curves sampled along the whole curve.
+    pub first: Vec<(f32, f32)>,
+    pub second: Vec<(f32, f32)>,
+    /// Control polygons of the derivative curves, one per polynomial segment.
+    pub first_control_points: Vec<Vec<(f32, f32)>>,
+    pub second_control_points: Vec<Vec<(f32, f32)>>,
+}
+
+/// Position and Frenet frame of the edited curve at the larp point, in window pixels.
+#[derive(Debug, Clone, Copy)]
+pub struct MotionFrame {
+    pub position: (f32, f32),
+    pub tangent: (f32, f32),
+    pub normal: (f32, f32),
+}
+
+/// Curve of the scene that takes part in an intersection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SceneCurve {
+    Edited,
+    Inactive(usize),
+    Line,
+}
+
+/// Intersection of two scene curves at the parameters `t1` of the first and `t2` of the second.
+#[derive(Debug, Clone, Copy)]
+pub struct SceneIntersection {
+    pub first: SceneCurve,
+    pub second: SceneCurve,
+    pub t1: f32,
+    pub t2: f32,
+    pub point: (f32, f32),
+}
+
+/// Everything the polyline of the edited curve and the other cached data of the curves depend on.
+/// The larp ratio is left out of the config, it only moves the larp point.
+#[derive(Debug, Clone, PartialEq)]
+struct PolylineKey {
+    config: EditorConfig,
+    window_size: (u32, u32),
+    control_points: Vec<(f32, f32)>,
+    weights: Vec<f32>,
+    continuity: Vec<Continuity>,
+    tangents: Vec<(f32, f32)>,
+    knots: Vec<f32>,
+    inactive_curves: Vec<BezierCurve>,
+    intersection_line: Option<((f32, f32), (f32, f32))>,
+}
+
+/// Everything an undoable edit can change.
+#[derive(Debug, Clone, PartialEq)]
+struct EditorSnapshot {
+    control_points: Vec<(f32, f32)>,
+    weights: Vec<f32>,
+    continuity: Vec<Continuity>,
+    tangents: Vec<(f32, f32)>,
+    knots: Vec<f32>,
+    inactive_curves: Vec<BezierCurve>,
+    intersection_line: Option<((f32, f32), (f32, f32))>,
+    /// Whether the control points are laid out in the easing unit square, which does not scale with the window.
+    is_easing_layout: bool,
+}
+
+/// Data derived from the curves, kept until the curves or the settings change. Each part is
+/// computed when it is first needed.
+#[derive(Default)]
+struct PolylineCache {
+    key: Option<PolylineKey>,
+    /// Polyline of the edited curve, valid when `has_polyline` is set. The buffer is reused.
+    polyline: Vec<(f32, f32)>,
+    has_polyline: bool,
+    arc_length: Option<Option<Rc<ArcLengthTable>>>,
+    inactive_arc_lengths: Vec<Option<Rc<ArcLengthTable>>>,
+    /// Curvature profiles over the parameter and over the arc length.
+    curvature_profiles: [Option<Rc<Vec<f32>>>; 2],
+    curvature_comb: Option<Rc<CombTeeth>>,
+    features: Option<Rc<CurveFeatures>>,
+    intersections: Option<Rc<Vec<SceneIntersection>>>,
+    stroke_outline: Option<Rc<Vec<BezierCurve>>>,
+    bezier_segments: Option<Rc<Segments>>,
+    pixel_segments: Option<Rc<Segments>>,
+    bounding_box: Option<Option<BoundingBox>>,
+    extrema_points: Option<Rc<Vec<(f32, f32)>>>,
+    hodograph: Option<Rc<Hodograph>>,
+    /// Values that also depend on the mouse position or the larp ratio, kept with the input they were computed for.
+    hover_projection: Option<((u32, u32), Option<Projection>)>,
+    motion_frame: Option<(f32, Option<MotionFrame>)>,
+}
+
+impl PolylineCache {
+    /// Drops everything computed for an older key.
+    fn reset(&mut self, key: PolylineKey) {
+        self.key = Some(key);
+        self.has_polyline = false;
+        self.arc_length = None;
+        self.inactive_arc_lengths.clear();
+        self.curvature_profiles = [None, None];
+        self.curvature_comb = None;
+        self.features = None;
+        self.intersections = None;
+        self.stroke_outline = None;
+        self.bezier_segments = None;
+        self.pixel_segments = None;
+        self.bounding_box = None;
+        self.extrema_points = None;
+        self.hodograph = None;
+        self.hover_projection = None;
+        self.motion_frame = None;
+    }
+
+    fn inactive_arc_length(&mut self, idx: usize) -> &mut Option<Rc<ArcLengthTable>> {
+        if self.inactive_arc_lengths.len() <= idx {
+            self.inactive_arc_lengths.resize(idx + 1, None);
+        }
+        &mut self.inactive_arc_lengths[idx]
+    }
+}
+
 pub struct EditorLayer {
     side_panel_width_ratio: f32,
     control_point_radius: u32,
@@ -9,8 +188,27 @@ pub struct EditorLayer {
     working_area_bottom_right: (u32, u32),
     window_size: (u32, u32),
     control_points_normalized: Vec<(f32, f32)>,
+    control_point_weights: Vec<f32>,
+    control_point_continuity: Vec<Continuity>,
+    control_point_tangents: Vec<(f32, f32)>,
+    knots: Vec<f32>,
     control_point_dragged: Option<usize>,
+    tangent_dragged: Option<usize>,
     last_mouse_pos: (u32, u32),
+    degree_reduction_deviation: Option<f32>,
+    inactive_curves: Vec<BezierCurve>,
+    intersection_line: Option<((f32, f32), (f32, f32))>,
+    line_endpoint_dragged: Option<usize>,
+    export_status: Option<String>,
+    freehand_samples: Option<Vec<(f32, f32)>>,
+    polyline_cache: RefCell<PolylineCache>,
+    bernstein_table: RefCell<Option<BernsteinTable>>,
+    history: History<EditorSnapshot>,
+    /// Label of the mouse gesture in progress and the state before it, recorded as one edit on release.
+    gesture: Option<(String, EditorSnapshot)>,
+    /// Consecutive edits of the same value merge in the history only within one session.
+    edit_session: u64,
+    last_scroll: Option<Instant>,
 }
 
 impl EditorLayer {
@@ -28,8 +226,25 @@ impl EditorLayer {
             working_area_bottom_right,
             window_size,
             control_points_normalized: Vec::new(),
+            control_point_weights: Vec::new(),
+            control_point_continuity: Vec::new(),
+            control_point_tangents: Vec::new(),
+            knots: Vec::new(),
             control_point_dragged: None,
-            last_mouse_pos: (0,0)
+            tangent_dragged: None,
+            last_mouse_pos: (0,0),
+            degree_reduction_deviation: None,
+            inactive_curves: Vec::new(),
+            intersection_line: None,
+            line_endpoint_dragged: None,
+            export_status: None,
+            freehand_samples: None,
+            polyline_cache: RefCell::default(),
+            bernstein_table: RefCell::new(None),
+            history: History::new(),
+            gesture: None,
+            edit_session: 0,
+            last_scroll: None,
         }
 
     }
@@ -59,153 +274,832 @@ impl EditorLayer {
         return (top_left_x.round() as u32, top_left_y.round() as u32);
     }
 
+    fn control_point_at(&self, mouse_pos: (u32, u32)) -> Option<usize> {
+        let radius = self.control_point_radius;
+        self.control_points_normalized.iter().position(|control_point| {
+            let cp = EditorLayer::from_normalized_control_point(*control_point, self.window_size);
+            cp.0 < mouse_pos.0 && mouse_pos.0 < cp.0 + 2*radius + 1 && cp.1 < mouse_pos.1 && mouse_pos.1 < cp.1 + 2*radius + 1
+        })
+    }
+
     fn handle_right_mouse_click(&mut self, mouse_pos: (u32, u32)) {
         if let Some(_) = self.control_point_dragged {
             return;
         }
 
-        let starting_vec_size = self.control_points_normalized.len();
-        let radius = self.control_point_radius;
-        let window_size = self.window_size;
-        self.control_points_normalized.retain_mut(|control_point| {
-            let cp = EditorLayer::from_normalized_control_point(*control_point, window_size);
+        if let Some(idx) = self.control_point_at(mouse_pos) {
+            self.remove_control_point(idx);
+            return;
+        }
+        let control_point = EditorLayer::to_normalized_control_point(
+            (mouse_pos.0 - self.control_point_radius, mouse_pos.1 - self.control_point_radius),
+            self.window_size
+        );
+        self.insert_control_point(self.control_points_normalized.len(), control_point);
+    }
 
-            !(cp.0 < mouse_pos.0 && mouse_pos.0 < cp.0 + 2*radius + 1 && cp.1 < mouse_pos.1 && mouse_pos.1 < cp.1 + 2*radius + 1)
-        });
-        if starting_vec_size != self.control_points_normalized.len() {
-            return
+    fn handle_left_mouse_click(&mut self, mouse_pos: (u32, u32), config: &EditorConfig) {
+        self.control_point_dragged = self.control_point_at(mouse_pos);
+        if self.control_point_dragged.is_none() && config.curve_mode == CurveMode::Hermite {
+            self.tangent_dragged = self.tangent_handle_at(mouse_pos);
+        }
+        if self.control_point_dragged.is_none() && self.tangent_dragged.is_none() {
+            // Inactive curves are plain Bezier curves, other modes would lose their per-point data.
+            if let Some((curve_idx, idx)) = self.inactive_control_point_at(mouse_pos).filter(|_| config.curve_mode == CurveMode::Bezier) {
+                self.activate_curve(curve_idx);
+                self.control_point_dragged = Some(idx);
+            } else {
+                self.line_endpoint_dragged = self.line_endpoint_at(mouse_pos);
+            }
         }
-        self.control_points_normalized.push(
-            EditorLayer::to_normalized_control_point(
+        if config.freehand && self.control_point_dragged.is_none() && self.tangent_dragged.is_none() && self.line_endpoint_dragged.is_none() {
+            let position = EditorLayer::to_normalized_control_point(
                 (mouse_pos.0 - self.control_point_radius, mouse_pos.1 - self.control_point_radius),
                 self.window_size
-            )
-        );
+            );
+            self.freehand_samples = Some(vec![position]);
+        }
     }
 
-    fn handle_left_mouse_click(&mut self, mouse_pos: (u32, u32)) {
-        let radius = self.control_point_radius;
-        let window_size = self.window_size;
-        for (idx, control_point) in self.control_points_normalized.iter().enumerate() {
-            let cp = EditorLayer::from_normalized_control_point(*control_point, window_size);
-            if cp.0 < mouse_pos.0 && mouse_pos.0 < cp.0 + 2*radius + 1 && cp.1 < mouse_pos.1 && mouse_pos.1 < cp.1 + 2*radius + 1 {
-                self.control_point_dragged = Some(idx);
-                break;
+    /// Name of the edit started by the last left click, if it started one.
+    fn gesture_label(&self) -> Option<String> {
+        if let Some(idx) = self.control_point_dragged {
+            Some(format!("Move P{}", idx))
+        } else if let Some(idx) = self.tangent_dragged {
+            Some(format!("Move tangent of P{}", idx))
+        } else if self.line_endpoint_dragged.is_some() {
+            Some(String::from("Move line"))
+        } else if self.freehand_samples.is_some() {
+            Some(String::from("Freehand sketch"))
+        } else {
+            None
+        }
+    }
+
+    /// Fits the sketch with Bezier segments of the path degree. In Add mode the sketch continues the
+    /// path: trailing points that do not complete a segment become handles of a segment that joins
+    /// the path to the start of the sketch.
+    fn finish_freehand(&mut self, samples: Vec<(f32, f32)>, config: &EditorConfig) {
+        let degree = config.path_segment_degree as usize;
+        let pixel_samples: Vec<(f32, f32)> = samples.iter().map(|point| self.to_pixels(*point)).collect();
+        let segments = match degree {
+            2 => fitting::fit_quadratics(&pixel_samples, config.freehand_tolerance),
+            _ => fitting::fit_cubics(&pixel_samples, config.freehand_tolerance),
+        };
+        let fitted: Vec<(f32, f32)> = segments.iter()
+            .enumerate()
+            .flat_map(|(idx, segment)| segment.control_points()[usize::from(idx > 0)..].to_vec())
+            .map(|point| self.pixels_to_normalized(point))
+            .collect();
+        let first = match fitted.first() {
+            Some(point) => *point,
+            None => return,
+        };
+
+        let (mut points, mut weights, mut continuity) = (Vec::new(), Vec::new(), Vec::new());
+        if let Some(&last) = self.control_points_normalized.last().filter(|_| !config.freehand_replaces) {
+            points = self.control_points_normalized.clone();
+            weights = self.control_point_weights.clone();
+            continuity = self.control_point_continuity.clone();
+            let handles = (points.len() - 1) % degree;
+            if handles == 0 && last == first {
+                points.pop();
+            } else {
+                let missing = degree - 1 - handles;
+                points.extend((1..=missing).map(|idx| lerp(last, first, idx as f32 / (missing + 1) as f32)));
+            }
+        }
+        let start = points.len();
+        points.extend(fitted);
+        weights.resize(points.len(), 1.0);
+        continuity.resize(points.len(), Continuity::Corner);
+        // Fitted segments that meet with matching tangent directions keep doing so while editing.
+        for joint in (start + degree..points.len() - 1).step_by(degree) {
+            if is_smooth_joint(self.to_pixels(points[joint - 1]), self.to_pixels(points[joint]), self.to_pixels(points[joint + 1])) {
+                continuity[joint] = Continuity::G1;
             }
         }
+        self.set_curve(BezierCurve::rational(points, weights));
+        self.control_point_continuity = continuity;
+    }
+
+    fn line_endpoint_at(&self, mouse_pos: (u32, u32)) -> Option<usize> {
+        let radius = self.control_point_radius;
+        let (start, end) = self.intersection_line?;
+        [start, end].iter().position(|endpoint| {
+            let cp = EditorLayer::from_normalized_control_point(*endpoint, self.window_size);
+            cp.0 < mouse_pos.0 && mouse_pos.0 < cp.0 + 2*radius + 1 && cp.1 < mouse_pos.1 && mouse_pos.1 < cp.1 + 2*radius + 1
+        })
+    }
+
+    /// Adds a line segment across the working area to intersect the curves with, or removes it.
+    fn toggle_intersection_line(&mut self) {
+        if self.intersection_line.take().is_some() {
+            self.line_endpoint_dragged = None;
+            return;
+        }
+
+        let (top_left, bottom_right) = (self.working_area_top_left, self.working_area_bottom_right);
+        let middle = (top_left.1 + bottom_right.1) / 2;
+        let start = EditorLayer::to_normalized_control_point((top_left.0 + 20, middle), self.window_size);
+        let end = EditorLayer::to_normalized_control_point((bottom_right.0 - 20, middle), self.window_size);
+        self.intersection_line = Some((start, end));
+    }
+
+    pub fn has_intersection_line(&self) -> bool {
+        self.intersection_line.is_some()
+    }
+
+    /// Curve and control point index of a control point of an inactive curve under the mouse.
+    fn inactive_control_point_at(&self, mouse_pos: (u32, u32)) -> Option<(usize, usize)> {
+        let radius = self.control_point_radius;
+        self.inactive_curves.iter().enumerate().find_map(|(curve_idx, curve)| {
+            curve.control_points().iter().position(|control_point| {
+                let cp = EditorLayer::from_normalized_control_point(*control_point, self.window_size);
+                cp.0 < mouse_pos.0 && mouse_pos.0 < cp.0 + 2*radius + 1 && cp.1 < mouse_pos.1 && mouse_pos.1 < cp.1 + 2*radius + 1
+            }).map(|idx| (curve_idx, idx))
+        })
+    }
+
+    /// Makes the inactive curve at `curve_idx` the edited one and puts the current curve aside.
+    fn activate_curve(&mut self, curve_idx: usize) {
+        let curve = self.inactive_curves.remove(curve_idx);
+        if !self.control_points_normalized.is_empty() {
+            self.inactive_curves.push(self.curve());
+        }
+        self.set_curve(curve);
+    }
+
+    /// Splits the edited curve at `t`, the part before `t` stays edited and the rest becomes an inactive curve.
+    fn split_curve(&mut self, t: f32) {
+        if self.control_points_normalized.len() < 2 {
+            return;
+        }
+
+        let (left, right) = self.curve().split(t);
+        self.set_curve(left);
+        self.inactive_curves.push(right);
+    }
+
+    pub fn control_point_count(&self) -> usize {
+        self.control_points_normalized.len()
+    }
+
+    /// Number of curves in the scene, including the edited one.
+    pub fn curve_count(&self) -> usize {
+        self.inactive_curves.len() + usize::from(!self.control_points_normalized.is_empty())
+    }
+
+    /// Inserts a control point with default per-point data at `idx`.
+    fn insert_control_point(&mut self, idx: usize, control_point: (f32, f32)) {
+        let tangent = match idx.checked_sub(1).map(|previous| self.control_points_normalized[previous]) {
+            Some(previous) => (control_point.0 - previous.0, control_point.1 - previous.1),
+            None => (DEFAULT_TANGENT_LENGTH, 0.0),
+        };
+        self.control_points_normalized.insert(idx, control_point);
+        self.control_point_weights.insert(idx, 1.0);
+        self.control_point_continuity.insert(idx, Continuity::Corner);
+        self.control_point_tangents.insert(idx, tangent);
+    }
+
+    fn remove_control_point(&mut self, idx: usize) {
+        self.control_points_normalized.remove(idx);
+        self.control_point_weights.remove(idx);
+        self.control_point_continuity.remove(idx);
+        self.control_point_tangents.remove(idx);
+    }
+
+    /// Position of the tangent handle of a Hermite point, drawn where the handle of the equivalent cubic Bezier segment would be.
+    fn tangent_handle(&self, idx: usize) -> (f32, f32) {
+        let (point, tangent) = (self.control_points_normalized[idx], self.control_point_tangents[idx]);
+        (point.0 + tangent.0 / 3.0, point.1 + tangent.1 / 3.0)
+    }
+
+    fn tangent_handle_at(&self, mouse_pos: (u32, u32)) -> Option<usize> {
+        let radius = self.control_point_radius;
+        (0..self.control_points_normalized.len()).find(|idx| {
+            let handle = EditorLayer::from_normalized_control_point(self.tangent_handle(*idx), self.window_size);
+            handle.0 < mouse_pos.0 && mouse_pos.0 < handle.0 + 2*radius + 1 && handle.1 < mouse_pos.1 && mouse_pos.1 < handle.1 + 2*radius + 1
+        })
+    }
+
+    fn handle_mouse_scroll(&mut self, delta: f32) {
+        if let Some(idx) = self.control_point_at(self.last_mouse_pos) {
+            let weight = self.control_point_weights[idx] * WEIGHT_SCROLL_FACTOR.powf(delta);
+            self.control_point_weights[idx] = weight.clamp(MIN_CONTROL_POINT_WEIGHT, MAX_CONTROL_POINT_WEIGHT);
+        }
+    }
+
+    fn load_quarter_circle_preset(&mut self) {
+        let (top_left, bottom_right) = (self.working_area_top_left, self.working_area_bottom_right);
+        let width = (bottom_right.0 - top_left.0) as f32;
+        let height = (bottom_right.1 - top_left.1) as f32;
+        let radius = 0.8 * width.min(height);
+        let center = (top_left.0 as f32 + 0.5 * (width + radius), top_left.1 as f32 + 0.5 * (height + radius));
+
+        // The circle is built in window pixels so that it stays round regardless of the aspect ratio.
+        // A negative radius puts the arc above and left of the center, which keeps it inside the working area.
+        let preset = BezierCurve::quarter_circle(center, -radius);
+        let (window_width, window_height) = (self.window_size.0 as f32, self.window_size.1 as f32);
+        let offset = self.control_point_radius as f32;
+        let control_points = preset.control_points().iter()
+            .map(|point| ((point.0 - offset) / window_width, -((point.1 - offset) / window_height)))
+            .collect();
+        self.set_curve(BezierCurve::rational(control_points, preset.weights().to_vec()));
+    }
+
+    /// Replaces all control points by the ones of `curve`, the other per-point data is reset.
+    fn set_curve(&mut self, curve: BezierCurve) {
+        let count = curve.control_points().len();
+        self.control_points_normalized = curve.control_points().to_vec();
+        self.control_point_weights = curve.weights().to_vec();
+        self.control_point_continuity = vec![Continuity::Corner; count];
+        self.control_point_tangents = vec![(DEFAULT_TANGENT_LENGTH, 0.0); count];
+        self.control_point_dragged = None;
+        self.tangent_dragged = None;
+    }
+
+    fn to_pixels(&self, point: (f32, f32)) -> (f32, f32) {
+        (point.0 * self.window_size.0 as f32, -point.1 * self.window_size.1 as f32)
+    }
+
+    fn pixels_to_normalized(&self, point: (f32, f32)) -> (f32, f32) {
+        (point.0 / self.window_size.0 as f32, -point.1 / self.window_size.1 as f32)
+    }
+
+    /// `curve` in window pixels, so that distances do not depend on the aspect ratio.
+    fn to_pixel_curve(&self, curve: &BezierCurve) -> BezierCurve {
+        let control_points = curve.control_points().iter().map(|point| self.to_pixels(*point)).collect();
+        BezierCurve::rational(control_points, curve.weights().to_vec())
+    }
+
+    /// Bottom left corner of the easing unit square in window pixels and its side length, centered in the working area.
+    fn easing_square(&self) -> ((f32, f32), f32) {
+        let (top_left, bottom_right) = (self.working_area_top_left, self.working_area_bottom_right);
+        let width = (bottom_right.0 - top_left.0) as f32;
+        let height = (bottom_right.1 - top_left.1) as f32;
+        let side = EASING_SQUARE_RATIO * width.min(height);
+        let origin = (top_left.0 as f32 + 0.5 * (width - side), top_left.1 as f32 + 0.5 * (height + side));
+        (origin, side)
+    }
+
+    /// Control point position of a point given in the coordinates of the easing unit square, where y points up.
+    fn unit_to_normalized(&self, point: (f32, f32)) -> (f32, f32) {
+        let (origin, side) = self.easing_square();
+        let offset = self.control_point_radius as f32;
+        self.pixels_to_normalized((origin.0 + point.0 * side - offset, origin.1 - point.1 * side - offset))
+    }
+
+    fn normalized_to_unit(&self, point: (f32, f32)) -> (f32, f32) {
+        let (origin, side) = self.easing_square();
+        let offset = self.control_point_radius as f32;
+        let pixels = self.to_pixels(point);
+        ((pixels.0 + offset - origin.0) / side, (origin.1 - pixels.1 - offset) / side)
+    }
+
+    /// Timing function described by the control points of the easing mode.
+    pub fn easing_curve(&self) -> Option<CubicBezierEasing> {
+        match self.control_points_normalized[..] {
+            [_, first, second, _] => Some(CubicBezierEasing::new(self.normalized_to_unit(first), self.normalized_to_unit(second))),
+            _ => None,
+        }
+    }
+
+    fn set_easing(&mut self, easing: CubicBezierEasing) {
+        let [first, second] = easing.handles();
+        let control_points = [(0.0, 0.0), first, second, (1.0, 1.0)].iter().map(|point| self.unit_to_normalized(*point)).collect();
+        self.set_curve(BezierCurve::new(control_points));
+    }
+
+    fn reduce_degree(&mut self) {
+        let curve = self.curve();
+        let reduced = curve.reduce_degree().filter(|reduced| {
+            reduced.weights().iter().all(|weight| (MIN_CONTROL_POINT_WEIGHT..=MAX_CONTROL_POINT_WEIGHT).contains(weight))
+        });
+        if let Some(reduced) = reduced {
+            let deviation = self.to_pixel_curve(&reduced).max_deviation(&self.to_pixel_curve(&curve), DEVIATION_SAMPLES);
+            self.set_curve(reduced);
+            self.degree_reduction_deviation = Some(deviation);
+        }
+    }
+
+    /// Largest distance in pixels between the curve before and after the last degree reduction.
+    pub fn degree_reduction_deviation(&self) -> Option<f32> {
+        self.degree_reduction_deviation
+    }
+
+    pub fn curve_degree(&self) -> usize {
+        self.curve().degree()
+    }
+
+    pub fn control_point_weights(&self) -> &[f32] {
+        &self.control_point_weights
+    }
+
+    pub fn path(&self, segment_degree: usize) -> BezierPath {
+        BezierPath::with_continuity(self.control_points_normalized.clone(), self.control_point_continuity.clone(), segment_degree)
+    }
+
+    /// Knot vector and degree shared by the B-spline and NURBS modes. The stored knots are used while
+    /// they fit the current point count and degree, after adding or removing points they are resized
+    /// to the new count. Only knots of another degree fall back to clamped knots.
+    fn knot_vector(&self, degree: usize) -> Option<(usize, Vec<f32>)> {
+        let count = self.control_points_normalized.len();
+        if count < 2 {
+            return None;
+        }
+
+        let degree = degree.min(count - 1);
+        if bspline::validate_knots(count, degree, &self.knots).is_ok() {
+            Some((degree, self.knots.clone()))
+        } else if let Some(knots) = bspline::resize_knots(&self.knots, degree, count) {
+            Some((degree, knots))
+        } else {
+            Some((degree, bspline::knot_vector(KnotVectorKind::Clamped, count, degree)))
+        }
+    }
+
+    pub fn bspline(&self, degree: usize) -> Option<BSplineCurve> {
+        let (degree, knots) = self.knot_vector(degree)?;
+        BSplineCurve::new(self.control_points_normalized.clone(), degree, knots).ok()
+    }
+
+    pub fn nurbs(&self, degree: usize) -> Option<NurbsCurve> {
+        let (degree, knots) = self.knot_vector(degree)?;
+        NurbsCurve::new(self.control_points_normalized.clone(), self.control_point_weights.clone(), degree, knots).ok()
+    }
+
+    /// Control point under the mouse cursor, or the one being dragged.
+    pub fn hovered_control_point(&self) -> Option<usize> {
+        self.control_point_dragged.or_else(|| self.control_point_at(self.last_mouse_pos))
+    }
+
+    fn insert_knot(&mut self, t: f32, degree: usize) {
+        let curve = match self.nurbs(degree) {
+            Some(curve) => curve,
+            None => return,
+        };
+        let (start, end) = curve.domain();
+        let u = start + t * (end - start);
+        if let Ok(refined) = curve.insert_knot(u) {
+            // Boehm's algorithm replaces the points `span - degree + 1..span` by one more, the new
+            // per-point data goes to the first of them.
+            let span = bspline::knot_span(curve.knots(), curve.degree(), curve.control_points().len(), u);
+            let inserted = span + 1 - curve.degree();
+            self.control_point_continuity.insert(inserted, Continuity::Corner);
+            self.control_point_tangents.insert(inserted, (DEFAULT_TANGENT_LENGTH, 0.0));
+            self.control_points_normalized = refined.control_points().to_vec();
+            self.control_point_weights = refined.weights().to_vec();
+            self.knots = refined.knots().to_vec();
+        }
+    }
+
+    /// Bezier path of the interpolating spline modes.
+    pub fn interpolating_path(&self, config: &EditorConfig) -> Option<BezierPath> {
+        let points = &self.control_points_normalized;
+        match config.curve_mode {
+            CurveMode::CatmullRom => Some(interpolating::catmull_rom(points, config.catmull_rom_parameterization)),
+            CurveMode::Hermite => Some(interpolating::hermite(points, &self.control_point_tangents)),
+            CurveMode::KochanekBartels => Some(interpolating::kochanek_bartels(points, config.tension, config.continuity, config.bias)),
+            _ => None,
+        }
+    }
+
+    /// Single Bezier curve through the points of the interpolation mode.
+    pub fn interpolated_bezier(&self, config: &EditorConfig) -> Option<BezierCurve> {
+        interpolating::bezier_through(&self.control_points_normalized, config.interpolation_parameterization)
+    }
+
+    fn move_control_point(&mut self, idx: usize, position: (f32, f32), config: &EditorConfig) {
+        match config.curve_mode {
+            CurveMode::Bezier | CurveMode::BSpline | CurveMode::Nurbs | CurveMode::CatmullRom |
+            CurveMode::Hermite | CurveMode::KochanekBartels | CurveMode::Interpolated => {
+                self.control_points_normalized[idx] = position;
+            },
+            CurveMode::Path => {
+                let mut path = self.path(config.path_segment_degree as usize);
+                path.move_point(idx, position);
+                self.control_points_normalized = path.points().to_vec();
+            },
+            CurveMode::Easing => {
+                // The end points stay at the corners of the unit square and the handles within its x range.
+                if idx == 0 || idx + 1 == self.control_points_normalized.len() {
+                    return;
+                }
+                let unit = self.normalized_to_unit(position);
+                self.control_points_normalized[idx] = self.unit_to_normalized((unit.0.clamp(0.0, 1.0), unit.1));
+            },
+        }
+    }
+
+    fn snapshot(&self, config: &EditorConfig) -> EditorSnapshot {
+        EditorSnapshot {
+            control_points: self.control_points_normalized.clone(),
+            weights: self.control_point_weights.clone(),
+            continuity: self.control_point_continuity.clone(),
+            tangents: self.control_point_tangents.clone(),
+            knots: self.knots.clone(),
+            inactive_curves: self.inactive_curves.clone(),
+            intersection_line: self.intersection_line,
+            is_easing_layout: config.curve_mode == CurveMode::Easing,
+        }
+    }
+
+    fn restore(&mut self, snapshot: EditorSnapshot) {
+        self.control_points_normalized = snapshot.control_points;
+        self.control_point_weights = snapshot.weights;
+        self.control_point_continuity = snapshot.continuity;
+        self.control_point_tangents = snapshot.tangents;
+        self.knots = snapshot.knots;
+        self.inactive_curves = snapshot.inactive_curves;
+        self.intersection_line = snapshot.intersection_line;
+        self.control_point_dragged = None;
+        self.tangent_dragged = None;
+        self.line_endpoint_dragged = None;
+    }
+
+    /// Records the change since `before` as one edit. If `merges`, it is merged with the previous edit
+    /// of the same label in the current edit session.
+    fn record(&mut self, label: String, before: EditorSnapshot, merges: bool, config: &EditorConfig) {
+        let after = self.snapshot(config);
+        if merges {
+            self.history.record_merging(label, self.edit_session, before, after);
+        } else {
+            self.history.record(label, before, after);
+        }
     }
 
-    pub fn handle_event(&mut self, event: ApplicationEvent, window: Window) {
+    /// Labels of the recorded edits, oldest first.
+    pub fn history_labels(&self) -> impl Iterator<Item = &str> {
+        self.history.labels()
+    }
+
+    /// Number of recorded edits that are applied, the rest were undone.
+    pub fn history_position(&self) -> usize {
+        self.history.position()
+    }
+
+    pub fn handle_command(&mut self, command: EditorCommand, config: &EditorConfig) {
+        // Undo is ignored mid-gesture, the gesture would record the restored state as its own.
+        let restored = match command {
+            EditorCommand::Undo | EditorCommand::Redo | EditorCommand::JumpToHistory { .. } if self.gesture.is_some() => None,
+            EditorCommand::Undo => self.history.undo().cloned(),
+            EditorCommand::Redo => self.history.redo().cloned(),
+            EditorCommand::JumpToHistory { position } => self.history.jump_to(position).cloned(),
+            EditorCommand::BeginEdit => {
+                self.edit_session += 1;
+                None
+            },
+            _ => {
+                let before = self.snapshot(config);
+                let (label, merges) = command_label(&command);
+                self.apply_command(command, config);
+                self.record(label, before, merges, config);
+                return;
+            },
+        };
+        if let Some(snapshot) = restored {
+            self.restore(snapshot);
+        }
+    }
+
+    fn apply_command(&mut self, command: EditorCommand, config: &EditorConfig) {
+        match command {
+            EditorCommand::SetControlPointWeight { index, weight } => {
+                if let Some(control_point_weight) = self.control_point_weights.get_mut(index) {
+                    *control_point_weight = weight.clamp(MIN_CONTROL_POINT_WEIGHT, MAX_CONTROL_POINT_WEIGHT);
+                }
+            },
+            EditorCommand::LoadQuarterCirclePreset => {
+                self.load_quarter_circle_preset();
+            },
+            EditorCommand::SetJointContinuity { index, continuity } => {
+                let mut path = self.path(config.path_segment_degree as usize);
+                if index < path.points().len() {
+                    path.set_continuity(index, continuity);
+                    self.control_points_normalized = path.points().to_vec();
+                    self.control_point_continuity = path.continuity().to_vec();
+                }
+            },
+            EditorCommand::SetKnot { index, value } => {
+                if let Some((degree, mut knots)) = self.knot_vector(config.bspline_degree as usize) {
+                    if index < knots.len() {
+                        let lower = if index > 0 { knots[index - 1] } else { 0.0 };
+                        let upper = if index + 1 < knots.len() { knots[index + 1] } else { 1.0 };
+                        knots[index] = value.clamp(lower, upper);
+                        // Edits that would empty the domain are rejected instead of resetting the knots later.
+                        if bspline::validate_knots(self.control_points_normalized.len(), degree, &knots).is_ok() {
+                            self.knots = knots;
+                        }
+                    }
+                }
+            },
+            EditorCommand::ResetKnots { kind } => {
+                if let Some((degree, _)) = self.knot_vector(config.bspline_degree as usize) {
+                    self.knots = bspline::knot_vector(kind, self.control_points_normalized.len(), degree);
+                }
+            },
+            EditorCommand::InsertKnot { t } => {
+                self.insert_knot(t, config.bspline_degree as usize);
+            },
+            EditorCommand::ElevateDegree => {
+                if !self.control_points_normalized.is_empty() {
+                    self.set_curve(self.curve().elevate_degree());
+                }
+            },
+            EditorCommand::ReduceDegree => {
+                self.reduce_degree();
+            },
+            EditorCommand::SplitAt { t } if config.curve_mode == CurveMode::Bezier => {
+                self.split_curve(t);
+            },
+            // The parts become plain Bezier curves, which would drop the data of the other modes.
+            EditorCommand::SplitAt { .. } => (),
+            EditorCommand::ToggleIntersectionLine => {
+                self.toggle_intersection_line();
+            },
+            EditorCommand::ExportStrokeOutline { path, overwrite } => {
+                self.export_stroke_outline(config, &path, overwrite);
+            },
+            EditorCommand::SetEasing { easing } => {
+                self.set_easing(easing);
+            },
+            EditorCommand::Undo | EditorCommand::Redo | EditorCommand::JumpToHistory { .. } | EditorCommand::BeginEdit => (),
+            EditorCommand::ResetTangents => {
+                let path = interpolating::catmull_rom(&self.control_points_normalized, CatmullRomParameterization::Uniform);
+                for segment in 0..path.segment_count() {
+                    let handles = path.segment(segment).control_points().to_vec();
+                    self.control_point_tangents[segment] = (3.0 * (handles[1].0 - handles[0].0), 3.0 * (handles[1].1 - handles[0].1));
+                    self.control_point_tangents[segment + 1] = (3.0 * (handles[3].0 - handles[2].0), 3.0 * (handles[3].1 - handles[2].1));
+                }
+            },
+        }
+    }
+
+    pub fn handle_event(&mut self, event: ApplicationEvent, window: Window, config: &EditorConfig) {
         match event {
             ApplicationEvent::MouseRightButtonPressed => {
                 if let Some(mouse_pos) = window.mouse_pos() {
-                    if self.is_in_working_area(mouse_pos) {
+                    // An easing curve always has exactly four control points.
+                    if self.is_in_working_area(mouse_pos) && config.curve_mode != CurveMode::Easing {
+                        self.edit_session += 1;
+                        let before = self.snapshot(config);
                         self.handle_right_mouse_click(mouse_pos);
+                        let label = if self.control_points_normalized.len() > before.control_points.len() { "Add point" } else { "Remove point" };
+                        self.record(String::from(label), before, false, config);
                     }
                 }
             },
             ApplicationEvent::MouseLeftButtonPressed => {
                 if let Some(mouse_pos) = window.mouse_pos() {
                     if self.is_in_working_area(mouse_pos) {
-                        self.handle_left_mouse_click(mouse_pos);
+                        self.edit_session += 1;
+                        let before = self.snapshot(config);
+                        self.handle_left_mouse_click(mouse_pos, config);
+                        self.gesture = self.gesture_label().map(|label| (label, before));
                     }
                 }
             },
             ApplicationEvent::MouseLeftButtonReleased => {
+                if let Some(samples) = self.freehand_samples.take() {
+                    self.finish_freehand(samples, config);
+                }
+                if let Some((label, before)) = self.gesture.take() {
+                    self.record(label, before, false, config);
+                }
                 self.control_point_dragged = None;
+                self.tangent_dragged = None;
+                self.line_endpoint_dragged = None;
             },
             ApplicationEvent::MouseMoved { x, y } => {
                 if !self.is_in_working_area((x,y)) {
                     self.control_point_dragged = None;
+                    self.tangent_dragged = None;
+                    self.line_endpoint_dragged = None;
                 } else {
+                    let position = EditorLayer::to_normalized_control_point((x - self.control_point_radius,y - self.control_point_radius), self.window_size);
                     if let Some(idx) = self.control_point_dragged {
-                        self.control_points_normalized[idx] = EditorLayer::to_normalized_control_point((x - self.control_point_radius,y - self.control_point_radius), self.window_size);
+                        self.move_control_point(idx, position, config);
+                    }
+                    if let Some(idx) = self.tangent_dragged {
+                        let point = self.control_points_normalized[idx];
+                        self.control_point_tangents[idx] = (3.0 * (position.0 - point.0), 3.0 * (position.1 - point.1));
+                    }
+                    if let (Some(idx), Some(line)) = (self.line_endpoint_dragged, self.intersection_line.as_mut()) {
+                        if idx == 0 { line.0 = position } else { line.1 = position }
+                    }
+                    if let Some(samples) = self.freehand_samples.as_mut() {
+                        samples.push(position);
                     }
                 }
 
                 self.last_mouse_pos = (x,y);
             },
+            // Only the rational modes use the weights.
+            ApplicationEvent::MouseScrolled { delta } if matches!(config.curve_mode, CurveMode::Bezier | CurveMode::Nurbs) => {
+                if self.last_scroll.is_none_or(|last| last.elapsed() > SCROLL_EDIT_TIMEOUT) {
+                    self.edit_session += 1;
+                }
+                self.last_scroll = Some(Instant::now());
+                let before = self.snapshot(config);
+                self.handle_mouse_scroll(delta);
+                if let Some(idx) = self.control_point_at(self.last_mouse_pos) {
+                    self.record(format!("Weight of P{}", idx), before, true, config);
+                }
+            },
             ApplicationEvent::WindowResized { width, height } => {
+                // The unit square does not scale like the normalized control points, so the easing is laid out
+                // again, and so are the easing curves in the history to keep undo from restoring them distorted.
+                let easing = self.easing_curve().filter(|_| config.curve_mode == CurveMode::Easing);
+                let mut history = std::mem::replace(&mut self.history, History::new());
+                let mut gesture = self.gesture.take();
+                for snapshot in easing_snapshots(&mut history, &mut gesture) {
+                    snapshot.control_points.iter_mut().for_each(|point| *point = self.normalized_to_unit(*point));
+                }
                 self.recalculate_canvas(width, height);
+                for snapshot in easing_snapshots(&mut history, &mut gesture) {
+                    snapshot.control_points.iter_mut().for_each(|point| *point = self.unit_to_normalized(*point));
+                }
+                self.history = history;
+                self.gesture = gesture;
+                if let Some(easing) = easing {
+                    self.set_easing(easing);
+                }
             },
             _ => ()
         }
     }
 
-    fn interpolated_point(&self, t: f32) -> (f32, f32) {
-        let mut buffer1 = Vec::<(f32, f32)>::with_capacity(self.control_points_normalized.len());
-        for idx in 0..(self.control_points_normalized.len() - 1){
-            buffer1.push(
-                (
-                    (1.0 - t) * self.control_points_normalized[idx].0 + t * self.control_points_normalized[idx+1].0,
-                    (1.0 - t) * self.control_points_normalized[idx].1 + t * self.control_points_normalized[idx+1].1
-                )
-            )
+    fn curve(&self) -> BezierCurve {
+        BezierCurve::rational(self.control_points_normalized.clone(), self.control_point_weights.clone())
+    }
+
+    fn to_screen_point(&self, point: (f32, f32)) -> (u32, u32) {
+        let point = EditorLayer::from_normalized_control_point(point, self.window_size);
+        (point.0 + self.control_point_radius, point.1 + self.control_point_radius)
+    }
+
+    /// Polyline through the curve, either flattened adaptively to the pixel tolerance
+    /// or sampled at a fixed number of parameters.
+    fn curve_polyline<F>(&self, evaluate: F, config: &EditorConfig) -> Vec<(f32, f32)>
+        where F: Fn(f32) -> Option<(f32, f32)> {
+        if config.adaptive_flattening {
+            return flatten::flatten(|t| evaluate(t).map(|point| self.to_pixels(point)), config.flattening_tolerance)
+                .into_iter()
+                .map(|point| self.pixels_to_normalized(point))
+                .collect();
+        }
+
+        let mut polyline = Vec::with_capacity(config.samples as usize + 1);
+        polyline.extend(evaluate(0.0));
+        let step = 1.0 / (config.samples as f32);
+        let mut t = step;
+        while t < 0.999 {
+            polyline.extend(evaluate(t));
+            t += step;
+        }
+        if !polyline.is_empty() {
+            polyline.extend(evaluate(1.0));
+        }
+        polyline
+    }
+
+    fn draw_curve<F>(&self, renderer: &mut Renderer, config: &EditorConfig, color: (f32, f32, f32), evaluate: F)
+        where F: Fn(f32) -> Option<(f32, f32)> {
+        self.draw_polyline(renderer, &self.curve_polyline(evaluate, config), color);
+    }
+
+    fn draw_polyline(&self, renderer: &mut Renderer, polyline: &[(f32, f32)], color: (f32, f32, f32)) {
+        let starting_point = match polyline.first() {
+            Some(point) => *point,
+            None => return,
+        };
+
+        renderer.begin_line_strip(self.to_screen_point(starting_point), color, 0.1);
+        for point in polyline.iter().skip(1) {
+            renderer.push_point(self.to_screen_point(*point));
         }
+        renderer.end_line_strip();
+    }
 
-        let mut buffer2 = Vec::<(f32, f32)>::with_capacity(self.control_points_normalized.len());
+    /// Empties the cache when the curves or the settings changed since it was filled.
+    fn refresh_polyline_cache(&self, config: &EditorConfig) {
+        let key = PolylineKey {
+            config: EditorConfig { larp_ratio: 0.0, ..*config },
+            window_size: self.window_size,
+            control_points: self.control_points_normalized.clone(),
+            weights: self.control_point_weights.clone(),
+            continuity: self.control_point_continuity.clone(),
+            tangents: self.control_point_tangents.clone(),
+            knots: self.knots.clone(),
+            inactive_curves: self.inactive_curves.clone(),
+            intersection_line: self.intersection_line,
+        };
+        let mut cache = self.polyline_cache.borrow_mut();
+        if cache.key.as_ref() != Some(&key) {
+            cache.reset(key);
+        }
+    }
 
-        while buffer1.len() > 1 {
-            for idx in 0..(buffer1.len() - 1){
-                buffer2.push(
-                    (
-                        (1.0 - t) * buffer1[idx].0 + t * buffer1[idx+1].0,
-                        (1.0 - t) * buffer1[idx].1 + t * buffer1[idx+1].1
-                    )
-                )
-            }
+    /// Value kept in the cache slot picked by `slot`, computed on first use. The computation runs
+    /// without holding the cache, so that it can use other cached data.
+    fn cached<T: Clone>(&self, config: &EditorConfig, slot: impl Fn(&mut PolylineCache) -> &mut Option<T>, compute: impl FnOnce() -> T) -> T {
+        self.refresh_polyline_cache(config);
+        if let Some(value) = slot(&mut self.polyline_cache.borrow_mut()).clone() {
+            return value;
+        }
+        let value = compute();
+        *slot(&mut self.polyline_cache.borrow_mut()) = Some(value.clone());
+        value
+    }
 
-            std::mem::swap(&mut buffer1, &mut buffer2);
-            buffer2.clear();
+    /// Like [`Self::cached`] for a value that also depends on `input`, which is recomputed when the input changes.
+    fn cached_for<K: PartialEq, T: Clone>(
+        &self,
+        config: &EditorConfig,
+        input: K,
+        slot: impl Fn(&mut PolylineCache) -> &mut Option<(K, T)>,
+        compute: impl FnOnce() -> T,
+    ) -> T {
+        self.refresh_polyline_cache(config);
+        if let Some((_, value)) = slot(&mut self.polyline_cache.borrow_mut()).as_ref().filter(|(cached, _)| *cached == input) {
+            return value.clone();
         }
+        let value = compute();
+        *slot(&mut self.polyline_cache.borrow_mut()) = Some((input, value.clone()));
+        value
+    }
 
-        return buffer1[0];
+    /// Polyline of the edited curve, recomputed only when the curve or the settings changed since the last call.
+    fn edited_polyline(&self, config: &EditorConfig) -> Ref<'_, [(f32, f32)]> {
+        self.refresh_polyline_cache(config);
+        if !self.polyline_cache.borrow().has_polyline {
+            let mut polyline = std::mem::take(&mut self.polyline_cache.borrow_mut().polyline);
+            self.compute_edited_polyline(config, &mut polyline);
+            let mut cache = self.polyline_cache.borrow_mut();
+            cache.polyline = polyline;
+            cache.has_polyline = true;
+        }
+        Ref::map(self.polyline_cache.borrow(), |cache| &cache.polyline[..])
     }
 
-    fn draw_bezier_curve(&self, renderer: &mut Renderer, samples: u32, color: (f32, f32, f32)) {
-        if self.control_points_normalized.len() <= 2 {
+    /// Samples the edited curve into `polyline`. A single Bezier curve at its own parameter is sampled
+    /// with a reused f64 evaluator, or at fixed samples optionally with a precomputed Bernstein table.
+    fn compute_edited_polyline(&self, config: &EditorConfig, polyline: &mut Vec<(f32, f32)>) {
+        let is_single_bezier = matches!(config.curve_mode, CurveMode::Bezier | CurveMode::Easing) && self.control_points_normalized.len() > 2;
+        if !is_single_bezier || config.constant_speed {
+            polyline.clear();
+            if let Some(evaluate) = self.evaluator(config) {
+                polyline.extend(self.curve_polyline(evaluate, config));
+            }
             return;
         }
 
-        let starting_point = EditorLayer::from_normalized_control_point(self.control_points_normalized[0], self.window_size);
-        let starting_point = (starting_point.0 + self.control_point_radius, starting_point.1 + self.control_point_radius);
-        renderer.begin_line_strip(starting_point, color, 0.1);
-        let step = 1.0 / (samples as f32);
-        let mut t = step;
-        while t < 0.999 {
-            let larped_point = self.interpolated_point(t);
-            let larped_point = EditorLayer::from_normalized_control_point(larped_point, self.window_size);
-            renderer.push_point((larped_point.0 + self.control_point_radius, larped_point.1 + self.control_point_radius));
-            t = t + step;
-        }
-        let end_point = *self.control_points_normalized.last().unwrap();
-        let end_point = EditorLayer::from_normalized_control_point(end_point, self.window_size);
-        renderer.push_point((end_point.0 + self.control_point_radius, end_point.1 + self.control_point_radius));
-        renderer.end_line_strip();
+        let curve = self.curve();
+        let samples = config.samples as usize;
+        if config.adaptive_flattening {
+            let evaluator = RefCell::new(BezierEvaluator::new(&curve));
+            *polyline = self.curve_polyline(|t| evaluator.borrow_mut().evaluate(t), config);
+        } else if config.bernstein_evaluation {
+            let mut table = self.bernstein_table.borrow_mut();
+            if !table.as_ref().is_some_and(|table| table.degree() == curve.degree() && table.samples() == samples) {
+                *table = Some(BernsteinTable::uniform(curve.degree(), samples));
+            }
+            if let Some(table) = table.as_ref() {
+                table.sample(&curve, polyline);
+            }
+        } else {
+            BezierEvaluator::new(&curve).sample(samples, polyline);
+        }
+    }
+
+    /// Number of vertices of the polyline the edited curve is drawn with.
+    pub fn curve_vertex_count(&self, config: &EditorConfig) -> usize {
+        self.edited_polyline(config).len()
     }
 
-    fn draw_larp_points_strip(&self, renderer: &mut Renderer, color: (f32, f32, f32)) {
-        if self.control_points_normalized.len() <= 1 {
+    fn draw_larp_points_strip(&self, renderer: &mut Renderer, control_points: &[(f32, f32)], color: (f32, f32, f32)) {
+        if control_points.len() <= 1 {
             return;
         }
 
-        let starting_point = EditorLayer::from_normalized_control_point(self.control_points_normalized[0], self.window_size);
+        let starting_point = EditorLayer::from_normalized_control_point(control_points[0], self.window_size);
         let starting_point = (starting_point.0 + self.control_point_radius, starting_point.1 + self.control_point_radius);
         renderer.begin_line_strip(starting_point, color, 0.0);
-        for control_point in self.control_points_normalized.iter().skip(1) {
+        for control_point in control_points.iter().skip(1) {
             let cp = EditorLayer::from_normalized_control_point(*control_point, self.window_size);
             renderer.push_point((cp.0 + self.control_point_radius, cp.1 + self.control_point_radius));
         }
         renderer.end_line_strip();
     }
 
-    fn draw_control_points(&self, renderer: &mut Renderer, color: (f32, f32, f32)) {
-        if self.control_points_normalized.len() == 0 {
+    fn draw_control_points(&self, renderer: &mut Renderer, control_points: &[(f32, f32)], color: (f32, f32, f32)) {
+        if control_points.is_empty() {
             return;
         }
 
         renderer.begin_quad_batch(color, 0.4);
-        for control_point in &self.control_points_normalized {
+        for control_point in control_points {
             renderer.push_quad(
                 EditorLayer::from_normalized_control_point(*control_point, self.window_size),
                 (2 * self.control_point_radius + 1, 2 * self.control_point_radius + 1));
@@ -213,23 +1107,689 @@ impl EditorLayer {
         renderer.end_quad_batch();
     }
 
-    fn draw_larp_point(&self, renderer: &mut Renderer, t: f32, color: (f32, f32, f32)) {
-        if self.control_points_normalized.len() <= 2 {
+    fn draw_tangent_handles(&self, renderer: &mut Renderer, line_color: (f32, f32, f32), handle_color: (f32, f32, f32)) {
+        for idx in 0..self.control_points_normalized.len() {
+            renderer.begin_line_strip(self.to_screen_point(self.control_points_normalized[idx]), line_color, 0.0);
+            renderer.push_point(self.to_screen_point(self.tangent_handle(idx)));
+            renderer.end_line_strip();
+        }
+
+        renderer.begin_quad_batch(handle_color, 0.4);
+        for idx in 0..self.control_points_normalized.len() {
+            renderer.push_quad(
+                EditorLayer::from_normalized_control_point(self.tangent_handle(idx), self.window_size),
+                (2 * self.control_point_radius + 1, 2 * self.control_point_radius + 1));
+        }
+        renderer.end_quad_batch();
+    }
+
+    /// Evaluation function over `[0, 1]` of the edited curve in the current mode.
+    fn parametric_curve(&self, config: &EditorConfig) -> Option<Evaluator> {
+        match config.curve_mode {
+            CurveMode::Bezier | CurveMode::Easing if self.control_points_normalized.len() > 2 => {
+                let curve = self.curve();
+                Some(Box::new(move |t| curve.evaluate(t)))
+            },
+            CurveMode::Bezier | CurveMode::Easing => None,
+            CurveMode::Path => {
+                let path = self.path(config.path_segment_degree as usize);
+                Some(Box::new(move |t| path.evaluate(t)))
+            },
+            CurveMode::BSpline => {
+                let curve = self.bspline(config.bspline_degree as usize)?;
+                Some(Box::new(move |t| Some(curve.evaluate_normalized(t))))
+            },
+            CurveMode::Nurbs => {
+                let curve = self.nurbs(config.bspline_degree as usize)?;
+                Some(Box::new(move |t| Some(curve.evaluate_normalized(t))))
+            },
+            CurveMode::CatmullRom | CurveMode::Hermite | CurveMode::KochanekBartels => {
+                let path = self.interpolating_path(config)?;
+                Some(Box::new(move |t| path.evaluate(t)))
+            },
+            CurveMode::Interpolated => {
+                let curve = self.interpolated_bezier(config)?;
+                Some(Box::new(move |t| curve.evaluate(t)))
+            },
+        }
+    }
+
+    /// Arc-length table of `evaluate` measured in window pixels.
+    fn arc_length_table(&self, evaluate: &dyn Fn(f32) -> Option<(f32, f32)>) -> ArcLengthTable {
+        ArcLengthTable::new(ARC_LENGTH_SAMPLES, |t| evaluate(t).map(|point| self.to_pixels(point)))
+    }
+
+    /// Cached arc-length table of the edited curve.
+    fn edited_arc_length(&self, config: &EditorConfig) -> Option<Rc<ArcLengthTable>> {
+        self.cached(config, |cache| &mut cache.arc_length, || {
+            self.parametric_curve(config).map(|evaluate| Rc::new(self.arc_length_table(&evaluate)))
+        })
+    }
+
+    /// Cached arc-length table of the inactive curve at `idx`.
+    fn inactive_arc_length(&self, config: &EditorConfig, idx: usize) -> Rc<ArcLengthTable> {
+        self.cached(config, |cache| cache.inactive_arc_length(idx), || {
+            let curve = &self.inactive_curves[idx];
+            Rc::new(self.arc_length_table(&|t| curve.evaluate(t)))
+        })
+    }
+
+    /// Reparameterizes `evaluate` by the fraction of its length when a table is given.
+    fn with_speed(evaluate: Evaluator, table: Option<Rc<ArcLengthTable>>) -> Evaluator {
+        match table {
+            Some(table) => Box::new(move |fraction| evaluate(table.parameter_at_fraction(fraction))),
+            None => evaluate,
+        }
+    }
+
+    /// Evaluation function used for drawing and the larp point of the edited curve.
+    fn evaluator(&self, config: &EditorConfig) -> Option<Evaluator> {
+        let evaluate = self.parametric_curve(config)?;
+        let table = if config.constant_speed { self.edited_arc_length(config) } else { None };
+        Some(EditorLayer::with_speed(evaluate, table))
+    }
+
+    /// Length of the edited curve in window pixels.
+    pub fn curve_length(&self, config: &EditorConfig) -> Option<f32> {
+        self.edited_arc_length(config).map(|table| table.total_length())
+    }
+
+    /// Bezier segments of the edited curve in the current mode, each with the range of the curve parameter it covers.
+    pub fn bezier_segments(&self, config: &EditorConfig) -> Rc<Segments> {
+        self.cached(config, |cache| &mut cache.bezier_segments, || Rc::new(self.compute_bezier_segments(config)))
+    }
+
+    fn compute_bezier_segments(&self, config: &EditorConfig) -> Segments {
+        match config.curve_mode {
+            CurveMode::Bezier | CurveMode::Easing if self.control_points_normalized.len() > 2 => vec![(self.curve(), (0.0, 1.0))],
+            CurveMode::Bezier | CurveMode::Easing => Vec::new(),
+            CurveMode::Path => self.path(config.path_segment_degree as usize).bezier_segments(),
+            CurveMode::BSpline => self.bspline(config.bspline_degree as usize).map(|curve| curve.bezier_segments()).unwrap_or_default(),
+            CurveMode::Nurbs => self.nurbs(config.bspline_degree as usize).map(|curve| curve.bezier_segments()).unwrap_or_default(),
+            CurveMode::CatmullRom | CurveMode::Hermite | CurveMode::KochanekBartels => {
+                self.interpolating_path(config).map(|path| path.bezier_segments()).unwrap_or_default()
+            },
+            CurveMode::Interpolated => self.interpolated_bezier(config).map(|curve| vec![(curve, (0.0, 1.0))]).unwrap_or_default(),
+        }
+    }
+
+    /// Every curve of the scene as Bezier segments in window pixels.
+    fn scene_curves(&self, config: &EditorConfig) -> Vec<(SceneCurve, Segments)> {
+        let to_pixels = |segments: Segments| -> Segments {
+            segments.into_iter().map(|(segment, range)| (self.to_pixel_curve(&segment), range)).collect()
+        };
+        let mut curves = vec![(SceneCurve::Edited, self.pixel_segments(config).to_vec())];
+        for (idx, curve) in self.inactive_curves.iter().enumerate() {
+            curves.push((SceneCurve::Inactive(idx), to_pixels(vec![(curve.clone(), (0.0, 1.0))])));
+        }
+        if let Some((start, end)) = self.intersection_line {
+            curves.push((SceneCurve::Line, to_pixels(vec![(BezierCurve::new(vec![start, end]), (0.0, 1.0))])));
+        }
+        curves
+    }
+
+    /// Intersections between every pair of distinct curves of the scene.
+    pub fn intersections(&self, config: &EditorConfig) -> Rc<Vec<SceneIntersection>> {
+        self.cached(config, |cache| &mut cache.intersections, || Rc::new(self.compute_intersections(config)))
+    }
+
+    fn compute_intersections(&self, config: &EditorConfig) -> Vec<SceneIntersection> {
+        let curves = self.scene_curves(config);
+        let mut intersections: Vec<SceneIntersection> = Vec::new();
+        for (first_idx, (first, first_segments)) in curves.iter().enumerate() {
+            for (second, second_segments) in curves.iter().skip(first_idx + 1) {
+                for (first_segment, first_range) in first_segments {
+                    for (second_segment, second_range) in second_segments {
+                        for found in intersection::curve_curve(first_segment, second_segment, INTERSECTION_TOLERANCE) {
+                            // Segments share their end points, so an intersection there is found twice.
+                            let is_known = intersections.iter().any(|known| {
+                                known.first == *first && known.second == *second &&
+                                (known.point.0 - found.point.0).abs() < 1.0 && (known.point.1 - found.point.1).abs() < 1.0
+                            });
+                            if !is_known {
+                                intersections.push(SceneIntersection {
+                                    first: *first,
+                                    second: *second,
+                                    t1: first_range.0 + found.t1 * (first_range.1 - first_range.0),
+                                    t2: second_range.0 + found.t2 * (second_range.1 - second_range.0),
+                                    point: found.point,
+                                });
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        for found in &mut intersections {
+            found.point = self.pixels_to_normalized(found.point);
+        }
+        intersections
+    }
+
+    /// Self-intersections, cusps and inflection points of the edited curve.
+    pub fn curve_features(&self, config: &EditorConfig) -> Rc<CurveFeatures> {
+        self.cached(config, |cache| &mut cache.features, || Rc::new(self.compute_curve_features(config)))
+    }
+
+    fn compute_curve_features(&self, config: &EditorConfig) -> CurveFeatures {
+        let segments = self.pixel_segments(config);
+        let mut features = CurveFeatures::default();
+        for (segment, range) in segments.iter() {
+            let located = |t: f32| {
+                let point = segment.evaluate(t).map(|point| self.pixels_to_normalized(point)).unwrap_or_default();
+                (range.0 + t * (range.1 - range.0), point)
+            };
+            features.cusps.extend(analysis::cusps(segment).into_iter().map(located));
+            features.inflections.extend(analysis::inflections(segment).into_iter().map(located));
+        }
+        features.self_intersections = analysis::self_intersections(&segments, INTERSECTION_TOLERANCE).into_iter()
+            .map(|found| Intersection { point: self.pixels_to_normalized(found.point), ..found })
+            .collect();
+        features
+    }
+
+    /// Point of the edited curve closest to the mouse cursor, when the cursor is near the curve.
+    /// The position is in window pixels, as is the distance.
+    pub fn hover_projection(&self, config: &EditorConfig) -> Option<Projection> {
+        if !self.is_in_working_area(self.last_mouse_pos) {
+            return None;
+        }
+        self.cached_for(config, self.last_mouse_pos, |cache| &mut cache.hover_projection, || self.compute_hover_projection(config))
+    }
+
+    fn compute_hover_projection(&self, config: &EditorConfig) -> Option<Projection> {
+        // Curve points are stored like control points, by the top left corner of their marker.
+        let radius = self.control_point_radius as f32;
+        let mouse = (self.last_mouse_pos.0 as f32 - radius, self.last_mouse_pos.1 as f32 - radius);
+        let closest = self.pixel_segments(config).iter()
+            .filter_map(|(segment, range)| {
+                let projection = segment.project(mouse)?;
+                Some(Projection { t: range.0 + projection.t * (range.1 - range.0), ..projection })
+            })
+            .min_by(|a, b| a.distance.total_cmp(&b.distance))?;
+        if closest.distance > HOVER_DISTANCE {
+            return None;
+        }
+        Some(closest)
+    }
+
+    /// Tight axis-aligned box around the edited curve, as its minimum and maximum corner.
+    fn bounding_box(&self, config: &EditorConfig) -> Option<BoundingBox> {
+        self.cached(config, |cache| &mut cache.bounding_box, || {
+            self.bezier_segments(config).iter()
+                .filter_map(|(segment, _)| analysis::bounding_box(segment))
+                .reduce(|a, b| ((a.0.0.min(b.0.0), a.0.1.min(b.0.1)), (a.1.0.max(b.1.0), a.1.1.max(b.1.1))))
+        })
+    }
+
+    /// Points of the edited curve where its x or y coordinate is extremal.
+    fn extrema_points(&self, config: &EditorConfig) -> Rc<Vec<(f32, f32)>> {
+        self.cached(config, |cache| &mut cache.extrema_points, || {
+            Rc::new(self.bezier_segments(config).iter()
+                .flat_map(|(segment, _)| {
+                    let (x_extrema, y_extrema) = analysis::extrema(segment);
+                    x_extrema.into_iter().chain(y_extrema).filter_map(|t| segment.evaluate(t)).collect::<Vec<_>>()
+                })
+                .collect())
+        })
+    }
+
+    /// The edited curve as Bezier segments in window pixels.
+    fn pixel_segments(&self, config: &EditorConfig) -> Rc<Segments> {
+        self.cached(config, |cache| &mut cache.pixel_segments, || {
+            Rc::new(self.bezier_segments(config).iter()
+                .map(|(segment, range)| (self.to_pixel_curve(segment), *range))
+                .collect())
+        })
+    }
+
+    /// Outline of the stroke along the edited curve as a closed chain of segments in window pixels.
+    pub fn stroke_outline(&self, config: &EditorConfig) -> Rc<Vec<BezierCurve>> {
+        self.cached(config, |cache| &mut cache.stroke_outline, || Rc::new(self.compute_stroke_outline(config)))
+    }
+
+    fn compute_stroke_outline(&self, config: &EditorConfig) -> Vec<BezierCurve> {
+        let segments: Vec<BezierCurve> = self.pixel_segments(config).iter().map(|(segment, _)| segment.clone()).collect();
+        let style = StrokeStyle {
+            width: config.stroke_width,
+            cap: config.stroke_cap,
+            join: config.stroke_join,
+            miter_limit: config.miter_limit,
+        };
+        offset::stroke_outline(&segments, &style, config.offset_tolerance)
+    }
+
+    fn draw_stroke_outline(&self, renderer: &mut Renderer, config: &EditorConfig, color: (f32, f32, f32)) {
+        for segment in self.stroke_outline(config).iter() {
+            self.draw_curve(renderer, config, color, |t| segment.evaluate(t).map(|point| self.pixels_to_normalized(point)));
+        }
+    }
+
+    /// Writes the stroke outline as an SVG document in window pixels. An existing file is only
+    /// replaced when `overwrite` is set.
+    fn export_stroke_outline(&mut self, config: &EditorConfig, path: &str, overwrite: bool) {
+        let outline = self.stroke_outline(config);
+        if outline.is_empty() {
+            self.export_status = Some("Nothing to export".to_string());
+            return;
+        }
+        if path.trim().is_empty() {
+            self.export_status = Some("Enter a file to export to".to_string());
+            return;
+        }
+
+        let path_data = svg::path_data(&outline, config.offset_tolerance);
+        let document = svg::document((self.window_size.0 as f32, self.window_size.1 as f32), &path_data);
+        let written = std::fs::OpenOptions::new()
+            .write(true)
+            .truncate(true)
+            .create(overwrite)
+            .create_new(!overwrite)
+            .open(path)
+            .and_then(|mut file| file.write_all(document.as_bytes()));
+        self.export_status = Some(match written {
+            Ok(()) => format!("Saved {} segments to {}", outline.len(), path),
+            Err(error) if error.kind() == std::io::ErrorKind::AlreadyExists => format!("{} already exists, enable overwrite to replace it", path),
+            Err(error) => format!("Export to {} failed: {}", path, error),
+        });
+    }
+
+    /// Result of the last export, for display.
+    pub fn export_status(&self) -> Option<&str> {
+        self.export_status.as_deref()
+    }
+
+    /// Curve parameters of `count` samples, evenly spaced in the parameter or along the curve.
+    fn sample_parameters(&self, segments: &Segments, count: usize, over_arc_length: bool) -> Vec<f32> {
+        let parameters = (0..count).map(|step| step as f32 / (count - 1) as f32);
+        if !over_arc_length {
+            return parameters.collect();
+        }
+
+        let table = ArcLengthTable::new(ARC_LENGTH_SAMPLES, |t| {
+            let (segment, _, local_t) = segment_at(segments, t)?;
+            segment.evaluate(local_t)
+        });
+        parameters.map(|fraction| table.parameter_at_fraction(fraction)).collect()
+    }
+
+    /// Signed curvature of the edited curve in 1/px at evenly spaced samples, for plotting.
+    pub fn curvature_profile(&self, config: &EditorConfig, over_arc_length: bool) -> Rc<Vec<f32>> {
+        self.cached(config, |cache| &mut cache.curvature_profiles[usize::from(over_arc_length)], || {
+            let segments = self.pixel_segments(config);
+            Rc::new(self.sample_parameters(&segments, CURVATURE_SAMPLES, over_arc_length).into_iter()
+                .filter_map(|t| segment_at(&segments, t))
+                .map(|(segment, _, local_t)| segment.curvature(local_t).unwrap_or(0.0))
+                .collect())
+        })
+    }
+
+    /// Teeth of the curvature comb as pairs of curve point and tooth tip, in window pixels.
+    /// The teeth point away from the center of curvature with a length of curvature times `scale`.
+    fn curvature_comb(&self, config: &EditorConfig) -> Rc<CombTeeth> {
+        self.cached(config, |cache| &mut cache.curvature_comb, || Rc::new(self.compute_curvature_comb(config)))
+    }
+
+    fn compute_curvature_comb(&self, config: &EditorConfig) -> CombTeeth {
+        let segments = self.pixel_segments(config);
+        self.sample_parameters(&segments, CURVATURE_SAMPLES, config.constant_speed).into_iter()
+            .filter_map(|t| {
+                let (segment, _, local_t) = segment_at(&segments, t)?;
+                let [point, d1, _] = segment.derivatives(local_t)?;
+                let curvature = segment.curvature(local_t)?;
+                let speed = (d1.0 * d1.0 + d1.1 * d1.1).sqrt();
+                let normal = (-d1.1 / speed, d1.0 / speed);
+                let length = curvature * config.curvature_comb_scale;
+                Some((point, (point.0 - normal.0 * length, point.1 - normal.1 * length)))
+            })
+            .collect()
+    }
+
+    fn draw_curvature_comb(&self, renderer: &mut Renderer, config: &EditorConfig) {
+        let color = (config.curvature_comb_color[0], config.curvature_comb_color[1], config.curvature_comb_color[2]);
+        let teeth = self.curvature_comb(config);
+        for (point, tip) in teeth.iter() {
+            renderer.begin_line_strip(self.to_screen_point(self.pixels_to_normalized(*point)), color, 0.2);
+            renderer.push_point(self.to_screen_point(self.pixels_to_normalized(*tip)));
+            renderer.end_line_strip();
+        }
+
+        let tips: Vec<(f32, f32)> = teeth.iter().map(|(_, tip)| self.pixels_to_normalized(*tip)).collect();
+        self.draw_larp_points_strip(renderer, &tips, color);
+    }
+
+    fn draw_polygon(&self, renderer: &mut Renderer, points: &[(f32, f32)], color: (f32, f32, f32)) {
+        let starting_point = match points.first() {
+            Some(point) => *point,
+            None => return,
+        };
+        renderer.begin_line_strip(self.to_screen_point(starting_point), color, 0.2);
+        for point in points.iter().skip(1).chain(std::iter::once(&starting_point)) {
+            renderer.push_point(self.to_screen_point(*point));
+        }
+        renderer.end_line_strip();
+    }
+
+    fn draw_intersection_line(&self, renderer: &mut Renderer, line_color: (f32, f32, f32), endpoint_color: (f32, f32, f32)) {
+        let (start, end) = match self.intersection_line {
+            Some(line) => line,
+            None => return,
+        };
+        renderer.begin_line_strip(self.to_screen_point(start), line_color, 0.1);
+        renderer.push_point(self.to_screen_point(end));
+        renderer.end_line_strip();
+        self.draw_control_points(renderer, &[start, end], endpoint_color);
+    }
+
+    fn draw_markers(&self, renderer: &mut Renderer, points: &[(f32, f32)], color: (f32, f32, f32)) {
+        if points.is_empty() {
+            return;
+        }
+
+        renderer.begin_quad_batch(color, 0.2);
+        for point in points {
+            renderer.push_quad(
+                EditorLayer::from_normalized_control_point(*point, self.window_size),
+                (2 * self.control_point_radius + 1, 2 * self.control_point_radius + 1));
+        }
+        renderer.end_quad_batch();
+    }
+
+    fn larp_point(&self, config: &EditorConfig) -> Option<(f32, f32)> {
+        self.evaluator(config).and_then(|evaluate| evaluate(config.larp_ratio))
+    }
+
+    /// Curve parameter of the larp point, which differs from the larp ratio at constant speed.
+    fn larp_parameter(&self, config: &EditorConfig) -> f32 {
+        match self.edited_arc_length(config).filter(|_| config.constant_speed) {
+            Some(table) => table.parameter_at_fraction(config.larp_ratio),
+            None => config.larp_ratio,
+        }
+    }
+
+    /// Position and derivatives with respect to the curve parameter `t`, in window pixels.
+    fn derivatives_at(&self, segments: &Segments, t: f32) -> Option<[(f32, f32); 3]> {
+        let (segment, range, local_t) = segment_at(segments, t)?;
+        let [point, d1, d2] = segment.derivatives(local_t)?;
+        let scale = 1.0 / (range.1 - range.0);
+        Some([point, (d1.0 * scale, d1.1 * scale), (d2.0 * scale * scale, d2.1 * scale * scale)])
+    }
+
+    /// Derivative curves of the edited curve, see [`Self::derivatives_at_larp`] for the larp point.
+    pub fn hodograph(&self, config: &EditorConfig) -> Rc<Hodograph> {
+        self.cached(config, |cache| &mut cache.hodograph, || Rc::new(self.compute_hodograph(config)))
+    }
+
+    fn compute_hodograph(&self, config: &EditorConfig) -> Hodograph {
+        let segments = self.pixel_segments(config);
+        let mut hodograph = Hodograph::default();
+        for step in 0..=HODOGRAPH_SAMPLES {
+            if let Some([_, d1, d2]) = self.derivatives_at(&segments, step as f32 / HODOGRAPH_SAMPLES as f32) {
+                hodograph.first.push(d1);
+                hodograph.second.push(d2);
+            }
+        }
+        for (segment, range) in segments.iter() {
+            let scale = 1.0 / (range.1 - range.0);
+            let scaled = |curve: &BezierCurve, factor: f32| -> Vec<(f32, f32)> {
+                curve.control_points().iter().map(|point| (point.0 * factor, point.1 * factor)).collect()
+            };
+            if let Some(first) = segment.hodograph() {
+                hodograph.first_control_points.push(scaled(&first, scale));
+                if let Some(second) = first.hodograph() {
+                    hodograph.second_control_points.push(scaled(&second, scale * scale));
+                }
+            }
+        }
+        hodograph
+    }
+
+    /// First and second derivative at the larp point, in window pixels.
+    pub fn derivatives_at_larp(&self, config: &EditorConfig) -> Option<((f32, f32), (f32, f32))> {
+        self.derivatives_at(&self.pixel_segments(config), self.larp_parameter(config)).map(|[_, d1, d2]| (d1, d2))
+    }
+
+    /// Tangent vector at the larp point, drawn at a third of its length like the handles of a cubic segment.
+    fn draw_tangent(&self, renderer: &mut Renderer, config: &EditorConfig, color: (f32, f32, f32)) {
+        let segments = self.pixel_segments(config);
+        let [point, d1, _] = match self.derivatives_at(&segments, self.larp_parameter(config)) {
+            Some(derivatives) => derivatives,
+            None => return,
+        };
+        self.draw_arrow(renderer, point, (point.0 + d1.0 / 3.0, point.1 + d1.1 / 3.0), color);
+    }
+
+    /// Arrow between two points in window pixels.
+    fn draw_arrow(&self, renderer: &mut Renderer, from: (f32, f32), tip: (f32, f32), color: (f32, f32, f32)) {
+        let (dx, dy) = (tip.0 - from.0, tip.1 - from.1);
+        let length = (dx * dx + dy * dy).sqrt();
+        if length <= f32::EPSILON {
             return;
         }
 
-        let larped_point = self.interpolated_point(t);
-        let larped_point = EditorLayer::from_normalized_control_point(larped_point, self.window_size);
+        let direction = (dx / length, dy / length);
+        let head = ARROW_HEAD_LENGTH;
+        let barbs = [
+            (tip.0 - head * (direction.0 - 0.5 * direction.1), tip.1 - head * (direction.1 + 0.5 * direction.0)),
+            (tip.0 - head * (direction.0 + 0.5 * direction.1), tip.1 - head * (direction.1 - 0.5 * direction.0)),
+        ];
+        renderer.begin_line_strip(self.to_screen_point(self.pixels_to_normalized(from)), color, 0.1);
+        renderer.push_point(self.to_screen_point(self.pixels_to_normalized(tip)));
+        renderer.push_point(self.to_screen_point(self.pixels_to_normalized(barbs[0])));
+        renderer.push_point(self.to_screen_point(self.pixels_to_normalized(barbs[1])));
+        renderer.push_point(self.to_screen_point(self.pixels_to_normalized(tip)));
+        renderer.end_line_strip();
+    }
+
+    pub fn motion_frame(&self, config: &EditorConfig) -> Option<MotionFrame> {
+        self.cached_for(config, config.larp_ratio, |cache| &mut cache.motion_frame, || {
+            let segments = self.pixel_segments(config);
+            let (segment, _, local_t) = segment_at(&segments, self.larp_parameter(config))?;
+            let (tangent, normal) = segment.frenet_frame(local_t)?;
+            Some(MotionFrame { position: segment.evaluate(local_t)?, tangent, normal })
+        })
+    }
+
+    /// Triangle at the larp point heading along the tangent, with the Frenet frame as arrows when enabled.
+    fn draw_motion_preview(&self, renderer: &mut Renderer, config: &EditorConfig) {
+        let MotionFrame { position, tangent, normal } = match self.motion_frame(config) {
+            Some(frame) => frame,
+            None => return,
+        };
+
+        // Pixel positions are the top left corners of markers, the triangle is centered like them.
+        let offset = self.control_point_radius as f32;
+        let corner = |along: f32, across: f32| (
+            position.0 + offset + config.motion_sprite_size * (along * tangent.0 + across * normal.0),
+            position.1 + offset + config.motion_sprite_size * (along * tangent.1 + across * normal.1),
+        );
+        renderer.begin_quad_batch((config.motion_sprite_color[0], config.motion_sprite_color[1], config.motion_sprite_color[2]), 0.25);
+        renderer.push_triangle([corner(2.0 / 3.0, 0.0), corner(-1.0 / 3.0, 0.4), corner(-1.0 / 3.0, -0.4)]);
+        renderer.end_quad_batch();
+
+        if config.show_frenet_frame {
+            let tip = |direction: (f32, f32)| (position.0 + FRENET_ARROW_LENGTH * direction.0, position.1 + FRENET_ARROW_LENGTH * direction.1);
+            self.draw_arrow(renderer, position, tip(tangent), (config.tangent_color[0], config.tangent_color[1], config.tangent_color[2]));
+            self.draw_arrow(renderer, position, tip(normal), (config.normal_color[0], config.normal_color[1], config.normal_color[2]));
+        }
+    }
+
+    /// Levels of the de Casteljau pyramid of the segment holding the larp point, without the control
+    /// polygon and the larp point itself. Each level gets a color between the configured first and last one.
+    fn draw_de_casteljau_pyramid(&self, renderer: &mut Renderer, config: &EditorConfig) {
+        let segments = self.bezier_segments(config);
+        let levels = match segment_at(&segments, self.larp_parameter(config)) {
+            Some((segment, _, local_t)) => segment.de_casteljau_levels(local_t),
+            None => return,
+        };
+        let (first, last) = (config.de_casteljau_first_color, config.de_casteljau_last_color);
+        let inner_levels = levels.len().saturating_sub(2);
+        for (idx, level) in levels.iter().enumerate().skip(1).take(inner_levels) {
+            let fraction = if inner_levels > 1 { (idx - 1) as f32 / (inner_levels - 1) as f32 } else { 0.0 };
+            let color = (
+                first[0] + fraction * (last[0] - first[0]),
+                first[1] + fraction * (last[1] - first[1]),
+                first[2] + fraction * (last[2] - first[2]),
+            );
+            self.draw_larp_points_strip(renderer, level, color);
+            self.draw_markers(renderer, level, color);
+        }
+    }
+
+    /// Outline of the easing unit square with the axes through its origin and ticks at every tenth.
+    fn draw_easing_axes(&self, renderer: &mut Renderer, color: (f32, f32, f32)) {
+        let corners = [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)].map(|corner| self.unit_to_normalized(corner));
+        self.draw_polygon(renderer, &corners, color);
+        let axes = [((-0.05, 0.0), (1.1, 0.0)), ((0.0, -0.05), (0.0, 1.1))];
+        let ticks = (1..EASING_TICKS).flat_map(|idx| {
+            let value = idx as f32 / EASING_TICKS as f32;
+            [((value, 0.0), (value, -0.02)), ((0.0, value), (-0.02, value))]
+        });
+        for (from, to) in axes.into_iter().chain(ticks) {
+            renderer.begin_line_strip(self.to_screen_point(self.unit_to_normalized(from)), color, 0.2);
+            renderer.push_point(self.to_screen_point(self.unit_to_normalized(to)));
+            renderer.end_line_strip();
+        }
+    }
+
+    fn draw_larp_point(&self, renderer: &mut Renderer, larped_point: Option<(f32, f32)>, color: (f32, f32, f32)) {
+        let larped_point = match larped_point {
+            Some(point) => EditorLayer::from_normalized_control_point(point, self.window_size),
+            None => return,
+        };
         renderer.begin_quad_batch(color, 0.3);
         renderer.push_quad((larped_point.0, larped_point.1), (2 * self.control_point_radius + 1, 2 * self.control_point_radius + 1));
         renderer.end_quad_batch();
     }
 
     pub fn render(&self, renderer: &mut Renderer, config: &EditorConfig) {
-        self.draw_larp_points_strip(renderer, (config.control_points_strip_color[0], config.control_points_strip_color[1], config.control_points_strip_color[2]));
-        self.draw_bezier_curve(renderer, config.samples as u32, (config.bezier_curve_color[0], config.bezier_curve_color[1], config.bezier_curve_color[2]));
-        self.draw_control_points(renderer, (config.control_points_color[0], config.control_points_color[1], config.control_points_color[2]));
-        self.draw_larp_point(renderer,  config.larp_ratio, (config.larp_point_color[0], config.larp_point_color[1], config.larp_point_color[2]));
+        let strip_color = (config.control_points_strip_color[0], config.control_points_strip_color[1], config.control_points_strip_color[2]);
+        let control_points_color = (config.control_points_color[0], config.control_points_color[1], config.control_points_color[2]);
+        let curve_color = (config.bezier_curve_color[0], config.bezier_curve_color[1], config.bezier_curve_color[2]);
+        for (idx, curve) in self.inactive_curves.iter().enumerate() {
+            self.draw_larp_points_strip(renderer, curve.control_points(), strip_color);
+            let owned_curve = curve.clone();
+            let table = if config.constant_speed { Some(self.inactive_arc_length(config, idx)) } else { None };
+            let evaluate = EditorLayer::with_speed(Box::new(move |t| owned_curve.evaluate(t)), table);
+            self.draw_curve(renderer, config, curve_color, evaluate);
+            self.draw_control_points(renderer, curve.control_points(), control_points_color);
+        }
+        if config.show_convex_hull {
+            let hull = analysis::convex_hull(&self.control_points_normalized);
+            self.draw_polygon(renderer, &hull, (config.convex_hull_color[0], config.convex_hull_color[1], config.convex_hull_color[2]));
+        }
+        if let Some((min, max)) = config.show_bounding_box.then(|| self.bounding_box(config)).flatten() {
+            let corners = [min, (max.0, min.1), max, (min.0, max.1)];
+            self.draw_polygon(renderer, &corners, (config.bounding_box_color[0], config.bounding_box_color[1], config.bounding_box_color[2]));
+        }
+        if config.curve_mode == CurveMode::Easing {
+            self.draw_easing_axes(renderer, (config.easing_axes_color[0], config.easing_axes_color[1], config.easing_axes_color[2]));
+        }
+        if config.show_stroke {
+            self.draw_stroke_outline(renderer, config, (config.stroke_color[0], config.stroke_color[1], config.stroke_color[2]));
+        }
+        if config.curve_mode != CurveMode::Interpolated {
+            self.draw_larp_points_strip(renderer, &self.control_points_normalized, strip_color);
+        } else if let Some(curve) = self.interpolated_bezier(config).filter(|_| config.show_interpolation_polygon) {
+            let color = (config.interpolation_polygon_color[0], config.interpolation_polygon_color[1], config.interpolation_polygon_color[2]);
+            self.draw_larp_points_strip(renderer, curve.control_points(), color);
+            self.draw_markers(renderer, curve.control_points(), color);
+        }
+        self.draw_polyline(renderer, &self.edited_polyline(config), curve_color);
+        if config.curve_mode == CurveMode::Hermite {
+            self.draw_tangent_handles(renderer, strip_color, control_points_color);
+        }
+        self.draw_control_points(renderer, &self.control_points_normalized, control_points_color);
+        if let Some(samples) = &self.freehand_samples {
+            self.draw_larp_points_strip(renderer, samples, curve_color);
+        }
+        self.draw_intersection_line(renderer, strip_color, control_points_color);
+        if config.show_intersections {
+            let points: Vec<(f32, f32)> = self.intersections(config).iter().map(|found| found.point).collect();
+            self.draw_markers(renderer, &points, (config.intersection_color[0], config.intersection_color[1], config.intersection_color[2]));
+        }
+        if config.show_curvature_comb {
+            self.draw_curvature_comb(renderer, config);
+        }
+        if config.show_tangent {
+            self.draw_tangent(renderer, config, (config.tangent_color[0], config.tangent_color[1], config.tangent_color[2]));
+        }
+        if config.show_extrema {
+            self.draw_markers(renderer, &self.extrema_points(config), (config.extrema_color[0], config.extrema_color[1], config.extrema_color[2]));
+        }
+        if let Some(projection) = config.show_closest_point.then(|| self.hover_projection(config)).flatten() {
+            let point = self.pixels_to_normalized(projection.point);
+            renderer.begin_line_strip((self.last_mouse_pos.0, self.last_mouse_pos.1), strip_color, 0.1);
+            renderer.push_point(self.to_screen_point(point));
+            renderer.end_line_strip();
+            self.draw_markers(renderer, &[point], (config.larp_point_color[0], config.larp_point_color[1], config.larp_point_color[2]));
+        }
+        if config.mark_curve_features {
+            let features = self.curve_features(config);
+            let self_intersections: Vec<(f32, f32)> = features.self_intersections.iter().map(|found| found.point).collect();
+            let cusps: Vec<(f32, f32)> = features.cusps.iter().map(|(_, point)| *point).collect();
+            let inflections: Vec<(f32, f32)> = features.inflections.iter().map(|(_, point)| *point).collect();
+            self.draw_markers(renderer, &self_intersections, (config.intersection_color[0], config.intersection_color[1], config.intersection_color[2]));
+            self.draw_markers(renderer, &cusps, (config.cusp_color[0], config.cusp_color[1], config.cusp_color[2]));
+            self.draw_markers(renderer, &inflections, (config.inflection_color[0], config.inflection_color[1], config.inflection_color[2]));
+        }
+        if config.show_de_casteljau {
+            self.draw_de_casteljau_pyramid(renderer, config);
+        }
+        self.draw_larp_point(renderer, self.larp_point(config), (config.larp_point_color[0], config.larp_point_color[1], config.larp_point_color[2]));
+        if config.show_motion_preview {
+            self.draw_motion_preview(renderer, config);
+        }
+    }
+
+}
+
+/// Name of the edit a command makes in the history and whether consecutive ones merge, like the
+/// commands sent every frame while a slider is dragged.
+fn command_label(command: &EditorCommand) -> (String, bool) {
+    match command {
+        EditorCommand::SetControlPointWeight { index, .. } => (format!("Weight of P{}", index), true),
+        EditorCommand::SetKnot { index, .. } => (format!("Move knot {}", index), true),
+        EditorCommand::SetJointContinuity { index, .. } => (format!("Continuity at P{}", index), false),
+        EditorCommand::LoadQuarterCirclePreset => (String::from("Quarter circle preset"), false),
+        EditorCommand::ResetKnots { .. } => (String::from("Reset knots"), false),
+        EditorCommand::InsertKnot { .. } => (String::from("Insert knot"), false),
+        EditorCommand::ResetTangents => (String::from("Reset tangents"), false),
+        EditorCommand::ElevateDegree => (String::from("Elevate degree"), false),
+        EditorCommand::ReduceDegree => (String::from("Reduce degree"), false),
+        EditorCommand::SplitAt { .. } => (String::from("Split curve"), false),
+        EditorCommand::ToggleIntersectionLine => (String::from("Toggle line"), false),
+        EditorCommand::SetEasing { .. } => (String::from("Set easing"), false),
+        EditorCommand::ExportStrokeOutline { .. } | EditorCommand::Undo | EditorCommand::Redo |
+        EditorCommand::JumpToHistory { .. } | EditorCommand::BeginEdit => {
+            (String::new(), false)
+        },
     }
+}
+
+/// Segment containing the curve parameter `t`, with its parameter range and the parameter within the segment.
+fn segment_at(segments: &Segments, t: f32) -> Option<(&BezierCurve, (f32, f32), f32)> {
+    let (segment, range) = segments.iter()
+        .find(|(_, range)| t <= range.1)
+        .or(segments.last())?;
+    Some((segment, *range, ((t - range.0) / (range.1 - range.0)).clamp(0.0, 1.0)))
+}
+
+/// Whether the handles `incoming` and `outgoing` lie on opposite sides of `anchor` on one line,
+/// in window pixels, so that the segments meeting there have matching tangent directions.
+fn is_smooth_joint(incoming: (f32, f32), anchor: (f32, f32), outgoing: (f32, f32)) -> bool {
+    let (a, b) = ((anchor.0 - incoming.0, anchor.1 - incoming.1), (outgoing.0 - anchor.0, outgoing.1 - anchor.1));
+    let lengths = (a.0 * a.0 + a.1 * a.1).sqrt() * (b.0 * b.0 + b.1 * b.1).sqrt();
+    lengths > f32::EPSILON && (a.0 * b.0 + a.1 * b.1) / lengths > SMOOTH_JOINT_COSINE
+}
 
+/// Snapshots of the history and of the running gesture whose control points are laid out in the easing unit square.
+fn easing_snapshots<'a>(
+    history: &'a mut History<EditorSnapshot>,
+    gesture: &'a mut Option<(String, EditorSnapshot)>,
+) -> impl Iterator<Item = &'a mut EditorSnapshot> {
+    history.states_mut()
+        .chain(gesture.as_mut().map(|(_, snapshot)| snapshot))
+        .filter(|snapshot| snapshot.is_easing_layout)
 }
@@ -226,6 +226,19 @@ impl QuadRenderer {
 
     }
 
+    pub fn push_triangle(&mut self, corners: [(f32, f32); 3]) {
+        if self.vertices.len() > self.max_buffer_size {
+            self.flush();
+        }
+
+        let first_index = self.vertices.len() as u32 / 2;
+        for (idx, corner) in corners.iter().enumerate() {
+            self.vertices.push(corner.0);
+            self.vertices.push(corner.1);
+            self.indices.push(first_index + idx as u32);
+        }
+    }
+
     fn flush(&mut self) {
         if self.vertices.len() == 0 {
             return;
@@ -366,6 +379,11 @@ impl Renderer {
         self.quad_renderer.push_quad(position, size);
     }
 
+    /// Filled triangle with corners in window pixels, drawn as part of the current quad batch.
+    pub fn push_triangle(&mut self, corners: [(f32, f32); 3]) {
+        self.quad_renderer.push_triangle(corners);
+    }
+
     pub fn begin_line_strip(&mut self, starting_point: (u32, u32), color: (f32, f32, f32), depth: f32) {
         self.line_renderer.begin_line_strip(&self.shader_program, self.ortho_matrix, starting_point, color, depth);
     }
@@ -2,14 +2,73 @@ use imgui;
 use std::time::Instant;
 use imgui_opengl_renderer::Renderer;
 
-use crate::app::editor_config::EditorConfig;
+use crate::app::editor_config::{EditorConfig, CurveMode};
 use crate::app::window_proxy::Window;
+use crate::app::editor_command::EditorCommand;
+use crate::app::application_event::ApplicationEvent;
+use crate::app::animation::{Animation, PlaybackMode};
+use crate::app::editor_layer::{EditorLayer, Hodograph, SceneCurve, MIN_CONTROL_POINT_WEIGHT, MAX_CONTROL_POINT_WEIGHT};
+use bezier_curve_editor::curve::path::Continuity;
+use bezier_curve_editor::curve::bspline::{self, KnotVectorKind};
+use bezier_curve_editor::curve::interpolating::CatmullRomParameterization;
+use bezier_curve_editor::curve::offset::{LineCap, LineJoin};
+use bezier_curve_editor::curve::easing::{CubicBezierEasing, Easing, EasingParseError};
+
+const CURVE_MODES: [CurveMode; 9] = [
+    CurveMode::Bezier,
+    CurveMode::Path,
+    CurveMode::BSpline,
+    CurveMode::Nurbs,
+    CurveMode::CatmullRom,
+    CurveMode::Hermite,
+    CurveMode::KochanekBartels,
+    CurveMode::Interpolated,
+    CurveMode::Easing,
+];
+const CONTINUITY_OPTIONS: [Continuity; 4] = [Continuity::Corner, Continuity::C1, Continuity::G1, Continuity::C2];
+const KEY_MAP: [(imgui::Key, glfw::Key); 21] = [
+    (imgui::Key::Tab, glfw::Key::Tab),
+    (imgui::Key::LeftArrow, glfw::Key::Left),
+    (imgui::Key::RightArrow, glfw::Key::Right),
+    (imgui::Key::UpArrow, glfw::Key::Up),
+    (imgui::Key::DownArrow, glfw::Key::Down),
+    (imgui::Key::PageUp, glfw::Key::PageUp),
+    (imgui::Key::PageDown, glfw::Key::PageDown),
+    (imgui::Key::Home, glfw::Key::Home),
+    (imgui::Key::End, glfw::Key::End),
+    (imgui::Key::Insert, glfw::Key::Insert),
+    (imgui::Key::Delete, glfw::Key::Delete),
+    (imgui::Key::Backspace, glfw::Key::Backspace),
+    (imgui::Key::Space, glfw::Key::Space),
+    (imgui::Key::Enter, glfw::Key::Enter),
+    (imgui::Key::Escape, glfw::Key::Escape),
+    (imgui::Key::A, glfw::Key::A),
+    (imgui::Key::C, glfw::Key::C),
+    (imgui::Key::V, glfw::Key::V),
+    (imgui::Key::X, glfw::Key::X),
+    (imgui::Key::Y, glfw::Key::Y),
+    (imgui::Key::Z, glfw::Key::Z),
+];
+const DEFAULT_EXPORT_PATH: &str = "stroke_outline.svg";
+/// Pause of the easing preview at the end of every run, in seconds.
+const EASING_PREVIEW_HOLD: f32 = 0.5;
 pub struct GUILayer {
     last_frame_time: Instant,
     imgui_renderer: Renderer,
     imgui_context: imgui::Context,
     editor_config: EditorConfig,
     side_panel_width_ratio: f32,
+    pending_commands: Vec<EditorCommand>,
+    animation: Animation,
+    easing_css: imgui::ImString,
+    easing_status: Option<String>,
+    easing_preview_time: f32,
+    export_path: imgui::ImString,
+    overwrite_export: bool,
+    /// Whether a weight or knot was being dragged in the last frame, so a new drag starts a new edit.
+    was_dragging_value: bool,
+    /// Easing mode was picked while there are points, which it would replace.
+    is_easing_switch_pending: bool,
 }
 
 impl GUILayer {
@@ -18,15 +77,71 @@ impl GUILayer {
         let imgui_renderer = Renderer::new(&mut imgui_context, |s| window_proxy.process_address(s) as _);
         imgui_context.style_mut().window_rounding = 0.0;
         imgui_context.io_mut().font_global_scale = 1.2;
+        for (imgui_key, glfw_key) in KEY_MAP {
+            imgui_context.io_mut().key_map[imgui_key as usize] = glfw_key as u32;
+        }
         Self {
             last_frame_time: Instant::now(),
             imgui_renderer,
             imgui_context,
             editor_config: EditorConfig::default(),
-            side_panel_width_ratio
+            side_panel_width_ratio,
+            pending_commands: Vec::new(),
+            animation: Animation::new(),
+            easing_css: imgui::ImString::with_capacity(64),
+            easing_status: None,
+            easing_preview_time: 0.0,
+            export_path: imgui::ImString::new(DEFAULT_EXPORT_PATH),
+            overwrite_export: false,
+            was_dragging_value: false,
+            is_easing_switch_pending: false,
         }
     }
 
+    /// Passes keyboard input on to the GUI, which needs it for text fields, and turns the undo
+    /// shortcuts into commands unless a text field has the focus.
+    pub fn handle_event(&mut self, event: ApplicationEvent) {
+        let imgui_io = self.imgui_context.io_mut();
+        let (key, is_down) = match event {
+            ApplicationEvent::KeyPressed { key, modifiers } => {
+                if key == glfw::Key::Z && modifiers.contains(glfw::Modifiers::Control) && !imgui_io.want_capture_keyboard {
+                    let command = if modifiers.contains(glfw::Modifiers::Shift) { EditorCommand::Redo } else { EditorCommand::Undo };
+                    self.pending_commands.push(command);
+                }
+                (key, true)
+            },
+            ApplicationEvent::KeyReleased { key } => (key, false),
+            ApplicationEvent::CharacterTyped { character } => {
+                imgui_io.add_input_character(character);
+                return;
+            },
+            // Scrolling over the side panel scrolls it, elsewhere the wheel belongs to the editor.
+            ApplicationEvent::MouseScrolled { delta } => {
+                if imgui_io.want_capture_mouse {
+                    imgui_io.mouse_wheel += delta;
+                }
+                return;
+            },
+            _ => return,
+        };
+        if let Some(key_down) = usize::try_from(key as i32).ok().and_then(|index| imgui_io.keys_down.get_mut(index)) {
+            *key_down = is_down;
+        }
+        let is_down = |key: glfw::Key| imgui_io.keys_down[key as usize];
+        let (ctrl, shift) = (
+            is_down(glfw::Key::LeftControl) || is_down(glfw::Key::RightControl),
+            is_down(glfw::Key::LeftShift) || is_down(glfw::Key::RightShift),
+        );
+        let (alt, super_key) = (
+            is_down(glfw::Key::LeftAlt) || is_down(glfw::Key::RightAlt),
+            is_down(glfw::Key::LeftSuper) || is_down(glfw::Key::RightSuper),
+        );
+        imgui_io.key_ctrl = ctrl;
+        imgui_io.key_shift = shift;
+        imgui_io.key_alt = alt;
+        imgui_io.key_super = super_key;
+    }
+
     pub fn handle_user_input(&mut self, window_proxy: Window) {
         let mut imgui_io = self.imgui_context.io_mut();
         let mut button_indeces: [bool; 5] = [false, false, false, false, false];
@@ -44,7 +159,11 @@ impl GUILayer {
         return &self.editor_config;
     }
 
-    pub fn render(&mut self, window_proxy: Window) {
+    pub fn take_commands(&mut self) -> Vec<EditorCommand> {
+        std::mem::take(&mut self.pending_commands)
+    }
+
+    pub fn render(&mut self, mut window_proxy: Window, editor_layer: &EditorLayer) {
         let imgui_io = self.imgui_context.io_mut();
 
         let now = Instant::now();
@@ -52,11 +171,17 @@ impl GUILayer {
         let delta_s = delta.as_secs() as f32 + delta.subsec_nanos() as f32 / 1_000_000_000.0;
         self.last_frame_time = now;
         imgui_io.delta_time = delta_s;
+        let config = &mut self.editor_config;
+        if let Some(progress) = self.animation.advance(delta_s, config.animation_duration, config.playback_mode) {
+            config.larp_ratio = config.animation_easing.apply(progress);
+        }
+        self.easing_preview_time = (self.easing_preview_time + delta_s) % (config.animation_duration + EASING_PREVIEW_HOLD);
 
         let window_size = window_proxy.size();
         imgui_io.display_size = [window_size.0 as f32, window_size.1 as f32];
         let side_panel_size = [window_size.0 as f32 * self.side_panel_width_ratio, window_size.1 as f32];
         let ui = self.imgui_context.frame();
+        let mut is_dragging_value = false;
 
         ui.window(imgui::im_str!("Side panel")  )
         .size(side_panel_size, imgui::Condition::Always)
@@ -71,11 +196,135 @@ impl GUILayer {
             ui.dummy([window_width, window_height * 0.01]);
             ui.text(format!("Frame time: {:.3}s [{} FPS]", delta_s, (1.0 / delta_s) as u32 ));
             ui.dummy([window_width, window_height * 0.02]);
+            ui.text("Curve mode:");
+            let curve_mode_names = [
+                imgui::im_str!("Bezier"),
+                imgui::im_str!("Bezier path"),
+                imgui::im_str!("B-spline"),
+                imgui::im_str!("NURBS"),
+                imgui::im_str!("Catmull-Rom"),
+                imgui::im_str!("Hermite"),
+                imgui::im_str!("Kochanek-Bartels"),
+                imgui::im_str!("Interpolate through points"),
+                imgui::im_str!("Easing curve"),
+            ];
+            let mut curve_mode = CURVE_MODES.iter().position(|mode| *mode == self.editor_config.curve_mode).unwrap() as i32;
+            if self.editor_config.freehand {
+                // Sketches are fitted with path segments, the mode stays locked while sketching.
+                ui.text_wrapped(imgui::im_str!("Bezier path, locked while sketching freehand."));
+            } else if ui.combo(imgui::im_str!("##curve mode"), &mut curve_mode, &curve_mode_names, CURVE_MODES.len() as i32) {
+                let curve_mode = CURVE_MODES[curve_mode as usize];
+                // The easing mode replaces the points, which the user confirms unless there are none.
+                self.is_easing_switch_pending = curve_mode == CurveMode::Easing && editor_layer.control_point_count() > 0;
+                if curve_mode == CurveMode::Easing && !self.is_easing_switch_pending {
+                    self.pending_commands.push(EditorCommand::SetEasing { easing: CubicBezierEasing::ease() });
+                }
+                if !self.is_easing_switch_pending {
+                    self.editor_config.curve_mode = curve_mode;
+                }
+            }
+            if self.is_easing_switch_pending {
+                ui.text_wrapped(imgui::im_str!("Replace the curve with an easing curve?"));
+                if ui.button(imgui::im_str!("Replace"), [window_width * 0.5, 0.0]) {
+                    self.editor_config.curve_mode = CurveMode::Easing;
+                    self.pending_commands.push(EditorCommand::SetEasing { easing: CubicBezierEasing::ease() });
+                    self.is_easing_switch_pending = false;
+                }
+                ui.same_line(0.0);
+                if ui.button(imgui::im_str!("Cancel"), [window_width * 0.5, 0.0]) {
+                    self.is_easing_switch_pending = false;
+                }
+            }
+            if ui.checkbox(imgui::im_str!("Freehand"), &mut self.editor_config.freehand) && self.editor_config.freehand {
+                self.editor_config.curve_mode = CurveMode::Path;
+                self.is_easing_switch_pending = false;
+            }
+            if self.editor_config.freehand {
+                ui.text("Sketches become segments of the path degree below.");
+                ui.text("Fit tolerance [px]:");
+                ui.slider_float(imgui::im_str!("##freehand tolerance"), &mut self.editor_config.freehand_tolerance, 0.5, 20.0).build();
+                ui.radio_button(imgui::im_str!("Replace"), &mut self.editor_config.freehand_replaces, true);
+                ui.same_line(0.0);
+                ui.radio_button(imgui::im_str!("Add"), &mut self.editor_config.freehand_replaces, false);
+            }
+            ui.dummy([window_width, window_height * 0.02]);
+            ui.text("History (Ctrl+Z, Ctrl+Shift+Z):");
+            if ui.button(imgui::im_str!("Undo"), [window_width * 0.5, 0.0]) {
+                self.pending_commands.push(EditorCommand::Undo);
+            }
+            ui.same_line(0.0);
+            if ui.button(imgui::im_str!("Redo"), [window_width * 0.5, 0.0]) {
+                self.pending_commands.push(EditorCommand::Redo);
+            }
+            ui.child_frame(imgui::im_str!("history"), [window_width, window_height * 0.12]).show_borders(true).build(|| {
+                let position = editor_layer.history_position();
+                let labels = std::iter::once("Start").chain(editor_layer.history_labels());
+                for (idx, label) in labels.enumerate() {
+                    // Undone edits are listed until the next edit replaces them.
+                    let text = if idx > position { imgui::im_str!("({})##history{}", label, idx) } else { imgui::im_str!("{}##history{}", label, idx) };
+                    if ui.selectable(&text, idx == position, imgui::ImGuiSelectableFlags::empty(), [0.0, 0.0]) {
+                        self.pending_commands.push(EditorCommand::JumpToHistory { position: idx });
+                    }
+                }
+            });
+            ui.dummy([window_width, window_height * 0.02]);
             ui.text("LARP Ratio:");
-            ui.slider_float(imgui::im_str!("LARP"), &mut self.editor_config.larp_ratio, 0.0, 1.0).build();
+            if ui.slider_float(imgui::im_str!("LARP"), &mut self.editor_config.larp_ratio, 0.0, 1.0).build() {
+                self.animation.pause();
+                // The ratio is eased, the animation continues from the progress that eases to it.
+                self.animation.seek(self.editor_config.animation_easing.invert(self.editor_config.larp_ratio));
+            }
+            let play_label = if self.animation.is_playing() { imgui::im_str!("Pause") } else { imgui::im_str!("Play") };
+            if ui.button(play_label, [window_width * 0.5, 0.0]) {
+                if self.animation.is_playing() {
+                    self.animation.pause();
+                } else {
+                    self.animation.play(self.editor_config.playback_mode);
+                }
+            }
+            ui.same_line(0.0);
+            if ui.button(imgui::im_str!("Stop"), [window_width * 0.5, 0.0]) {
+                self.animation.stop();
+                self.editor_config.larp_ratio = self.editor_config.animation_easing.apply(0.0);
+            }
+            ui.radio_button(imgui::im_str!("Once"), &mut self.editor_config.playback_mode, PlaybackMode::Once);
+            ui.same_line(0.0);
+            ui.radio_button(imgui::im_str!("Loop"), &mut self.editor_config.playback_mode, PlaybackMode::Loop);
+            ui.same_line(0.0);
+            ui.radio_button(imgui::im_str!("Ping-pong"), &mut self.editor_config.playback_mode, PlaybackMode::PingPong);
+            ui.text("Duration [s]:");
+            ui.slider_float(imgui::im_str!("##animation duration"), &mut self.editor_config.animation_duration, 0.5, 20.0).build();
+            ui.text(&imgui::im_str!("Time: {:.2} s", self.animation.progress() * self.editor_config.animation_duration));
+            ui.text("Easing:");
+            let easing = &mut self.editor_config.animation_easing;
+            ui.radio_button(imgui::im_str!("Linear"), easing, Easing::Linear);
+            ui.same_line(0.0);
+            ui.radio_button(imgui::im_str!("In"), easing, Easing::EaseIn);
+            ui.same_line(0.0);
+            ui.radio_button(imgui::im_str!("Out"), easing, Easing::EaseOut);
+            ui.same_line(0.0);
+            ui.radio_button(imgui::im_str!("In-out"), easing, Easing::EaseInOut);
+            ui.checkbox(imgui::im_str!("Constant speed"), &mut self.editor_config.constant_speed);
+            ui.checkbox(imgui::im_str!("De Casteljau construction"), &mut self.editor_config.show_de_casteljau);
+            if self.editor_config.show_de_casteljau {
+                ui.text("First and last level colors:");
+                ui.color_edit(imgui::im_str!("first level color"), &mut self.editor_config.de_casteljau_first_color).flags(imgui::ImGuiColorEditFlags::NoSmallPreview).build();
+                ui.color_edit(imgui::im_str!("last level color"), &mut self.editor_config.de_casteljau_last_color).flags(imgui::ImGuiColorEditFlags::NoSmallPreview).build();
+            }
+            if let Some(length) = editor_layer.curve_length(&self.editor_config) {
+                ui.text(&imgui::im_str!("Curve length: {:.1} px", length));
+            }
             ui.dummy([window_width, window_height * 0.02]);
-            ui.text("Curve sample points:");
-            ui.slider_int(imgui::im_str!("Samples"), &mut self.editor_config.samples, 10, 1000).build();
+            ui.checkbox(imgui::im_str!("Adaptive flattening"), &mut self.editor_config.adaptive_flattening);
+            if self.editor_config.adaptive_flattening {
+                ui.text("Tolerance [px]:");
+                ui.slider_float(imgui::im_str!("##flattening tolerance"), &mut self.editor_config.flattening_tolerance, 0.05, 5.0).build();
+            } else {
+                ui.text("Curve sample points:");
+                ui.slider_int(imgui::im_str!("Samples"), &mut self.editor_config.samples, 10, 1000).build();
+                ui.checkbox(imgui::im_str!("Precomputed Bernstein basis"), &mut self.editor_config.bernstein_evaluation);
+            }
+            ui.text(&imgui::im_str!("Curve vertices: {}", editor_layer.curve_vertex_count(&self.editor_config)));
             ui.dummy([window_width, window_height * 0.02]);
             ui.text("Curve color:");
             ui.color_edit(imgui::im_str!("beier curve color"), &mut self.editor_config.bezier_curve_color).flags(imgui::ImGuiColorEditFlags::NoSmallPreview).build();
@@ -88,8 +337,473 @@ impl GUILayer {
             ui.dummy([window_width, window_height * 0.02]);
             ui.text("LARP point color:");
             ui.color_edit(imgui::im_str!("larp point color"), &mut self.editor_config.larp_point_color).flags(imgui::ImGuiColorEditFlags::NoSmallPreview).build();
-
+            match self.editor_config.curve_mode {
+                CurveMode::Bezier => {
+                    ui.dummy([window_width, window_height * 0.02]);
+                    let (edited, is_active) = weight_sliders(&ui, editor_layer);
+                    is_dragging_value |= is_active;
+                    if let Some((index, weight)) = edited {
+                        push_drag_edit(&mut self.pending_commands, EditorCommand::SetControlPointWeight { index, weight }, self.was_dragging_value);
+                    }
+                    ui.dummy([window_width, window_height * 0.02]);
+                    if ui.button(imgui::im_str!("Quarter circle preset"), [window_width, 0.0]) {
+                        self.pending_commands.push(EditorCommand::LoadQuarterCirclePreset);
+                    }
+                    ui.dummy([window_width, window_height * 0.02]);
+                    ui.text(&imgui::im_str!("Degree: {}", editor_layer.curve_degree()));
+                    if ui.button(imgui::im_str!("Elevate degree"), [window_width * 0.5, 0.0]) {
+                        self.pending_commands.push(EditorCommand::ElevateDegree);
+                    }
+                    ui.same_line(0.0);
+                    if ui.button(imgui::im_str!("Reduce degree"), [window_width * 0.5, 0.0]) {
+                        self.pending_commands.push(EditorCommand::ReduceDegree);
+                    }
+                    if let Some(deviation) = editor_layer.degree_reduction_deviation() {
+                        ui.text(&imgui::im_str!("Last reduction max deviation: {:.2} px", deviation));
+                    }
+                    ui.dummy([window_width, window_height * 0.02]);
+                    if ui.button(imgui::im_str!("Split at t"), [window_width, 0.0]) {
+                        self.pending_commands.push(EditorCommand::SplitAt { t: self.editor_config.larp_ratio });
+                    }
+                    if editor_layer.curve_count() > 1 {
+                        ui.text(&imgui::im_str!("Curves: {}", editor_layer.curve_count()));
+                        ui.text("Drag a point of another curve to edit it.");
+                    }
+                },
+                CurveMode::Path => {
+                    ui.dummy([window_width, window_height * 0.02]);
+                    ui.text("Segment degree:");
+                    ui.radio_button(imgui::im_str!("Quadratic"), &mut self.editor_config.path_segment_degree, 2);
+                    ui.same_line(0.0);
+                    ui.radio_button(imgui::im_str!("Cubic"), &mut self.editor_config.path_segment_degree, 3);
+                    ui.dummy([window_width, window_height * 0.02]);
+                    ui.text("Joint continuity:");
+                    let path = editor_layer.path(self.editor_config.path_segment_degree as usize);
+                    let continuity_names = [imgui::im_str!("Corner"), imgui::im_str!("C1"), imgui::im_str!("G1"), imgui::im_str!("C2")];
+                    for index in path.joints() {
+                        ui.text(format!("P{}:", index));
+                        ui.same_line(0.0);
+                        let mut selected = CONTINUITY_OPTIONS.iter().position(|continuity| *continuity == path.continuity()[index]).unwrap() as i32;
+                        if ui.combo(&imgui::im_str!("##joint{}", index), &mut selected, &continuity_names, 4) {
+                            self.pending_commands.push(EditorCommand::SetJointContinuity { index, continuity: CONTINUITY_OPTIONS[selected as usize] });
+                        }
+                    }
+                },
+                CurveMode::BSpline | CurveMode::Nurbs => {
+                    let degree = self.editor_config.bspline_degree as usize;
+                    ui.dummy([window_width, window_height * 0.02]);
+                    ui.text("Degree:");
+                    ui.slider_int(imgui::im_str!("Degree"), &mut self.editor_config.bspline_degree, 1, 7).build();
+                    ui.dummy([window_width, window_height * 0.02]);
+                    ui.text("Knots:");
+                    if let Some(curve) = editor_layer.bspline(degree) {
+                        let (dragged, is_active) = knot_ticks(&ui, curve.knots(), [window_width, window_height * 0.04]);
+                        is_dragging_value |= is_active;
+                        if let Some((index, value)) = dragged {
+                            push_drag_edit(&mut self.pending_commands, EditorCommand::SetKnot { index, value }, self.was_dragging_value);
+                        }
+                        let mut distinct_knots = curve.knots().to_vec();
+                        distinct_knots.dedup();
+                        for knot in distinct_knots {
+                            ui.text(format!("u = {:.3} (multiplicity {})", knot, bspline::knot_multiplicity(curve.knots(), knot)));
+                        }
+                    }
+                    if ui.button(imgui::im_str!("Open knots"), [window_width * 0.48, 0.0]) {
+                        self.pending_commands.push(EditorCommand::ResetKnots { kind: KnotVectorKind::Uniform });
+                    }
+                    ui.same_line(0.0);
+                    if ui.button(imgui::im_str!("Clamped knots"), [window_width * 0.48, 0.0]) {
+                        self.pending_commands.push(EditorCommand::ResetKnots { kind: KnotVectorKind::Clamped });
+                    }
+                    if self.editor_config.curve_mode == CurveMode::Nurbs {
+                        if let Some(curve) = editor_layer.nurbs(degree) {
+                            let (start, end) = curve.domain();
+                            let u = start + self.editor_config.larp_ratio * (end - start);
+                            ui.text(format!("LARP knot u = {:.3} (multiplicity {})", u, bspline::knot_multiplicity(curve.knots(), u)));
+                            if ui.button(imgui::im_str!("Insert knot at LARP"), [window_width, 0.0]) {
+                                self.pending_commands.push(EditorCommand::InsertKnot { t: self.editor_config.larp_ratio });
+                            }
+                        }
+                        ui.dummy([window_width, window_height * 0.02]);
+                        let (edited, is_active) = weight_sliders(&ui, editor_layer);
+                        is_dragging_value |= is_active;
+                        if let Some((index, weight)) = edited {
+                            push_drag_edit(&mut self.pending_commands, EditorCommand::SetControlPointWeight { index, weight }, self.was_dragging_value);
+                        }
+                    }
+                },
+                CurveMode::CatmullRom => {
+                    ui.dummy([window_width, window_height * 0.02]);
+                    ui.text("Parameterization:");
+                    let parameterization = &mut self.editor_config.catmull_rom_parameterization;
+                    ui.radio_button(imgui::im_str!("Uniform"), parameterization, CatmullRomParameterization::Uniform);
+                    ui.same_line(0.0);
+                    ui.radio_button(imgui::im_str!("Centripetal"), parameterization, CatmullRomParameterization::Centripetal);
+                    ui.same_line(0.0);
+                    ui.radio_button(imgui::im_str!("Chordal"), parameterization, CatmullRomParameterization::Chordal);
+                },
+                CurveMode::Hermite => {
+                    ui.dummy([window_width, window_height * 0.02]);
+                    ui.text("Drag the tangent handles to edit tangents.");
+                    if ui.button(imgui::im_str!("Reset tangents"), [window_width, 0.0]) {
+                        self.pending_commands.push(EditorCommand::ResetTangents);
+                    }
+                },
+                CurveMode::KochanekBartels => {
+                    ui.dummy([window_width, window_height * 0.02]);
+                    ui.text("Tension:");
+                    ui.slider_float(imgui::im_str!("Tension"), &mut self.editor_config.tension, -1.0, 1.0).build();
+                    ui.text("Continuity:");
+                    ui.slider_float(imgui::im_str!("Continuity"), &mut self.editor_config.continuity, -1.0, 1.0).build();
+                    ui.text("Bias:");
+                    ui.slider_float(imgui::im_str!("Bias"), &mut self.editor_config.bias, -1.0, 1.0).build();
+                },
+                CurveMode::Interpolated => {
+                    ui.dummy([window_width, window_height * 0.02]);
+                    ui.text("Parameterization:");
+                    let parameterization = &mut self.editor_config.interpolation_parameterization;
+                    ui.radio_button(imgui::im_str!("Chord length"), parameterization, CatmullRomParameterization::Chordal);
+                    ui.same_line(0.0);
+                    ui.radio_button(imgui::im_str!("Centripetal##interpolation"), parameterization, CatmullRomParameterization::Centripetal);
+                    if let Some(curve) = editor_layer.interpolated_bezier(&self.editor_config) {
+                        ui.text(&imgui::im_str!("Degree: {}", curve.degree()));
+                    }
+                    ui.checkbox(imgui::im_str!("Control polygon"), &mut self.editor_config.show_interpolation_polygon);
+                    if self.editor_config.show_interpolation_polygon {
+                        ui.color_edit(imgui::im_str!("control polygon color"), &mut self.editor_config.interpolation_polygon_color).flags(imgui::ImGuiColorEditFlags::NoSmallPreview).build();
+                    }
+                },
+                CurveMode::Easing => {
+                    ui.dummy([window_width, window_height * 0.02]);
+                    ui.text("Drag the handles, x stays within [0, 1].");
+                    if let Some(easing) = editor_layer.easing_curve() {
+                        let css = easing.to_css();
+                        ui.text(&css);
+                        if ui.button(imgui::im_str!("Copy CSS"), [window_width, 0.0]) {
+                            window_proxy.set_clipboard_text(&css);
+                            self.easing_status = Some(String::from("Copied to the clipboard."));
+                        }
+                        let x = self.editor_config.larp_ratio;
+                        ui.text(&imgui::im_str!("y at x = {:.3}: {:.4}", x, easing.y_for_x(x)));
+                        ui.text("Preview:");
+                        let progress = (self.easing_preview_time / self.editor_config.animation_duration).min(1.0);
+                        easing_preview(&ui, &easing, progress, [window_width, window_height * 0.05]);
+                    }
+                    ui.text("Import CSS:");
+                    let mut import = ui.input_text(imgui::im_str!("##easing css"), &mut self.easing_css).enter_returns_true(true).build();
+                    import |= ui.button(imgui::im_str!("Import"), [window_width * 0.5, 0.0]);
+                    ui.same_line(0.0);
+                    if ui.button(imgui::im_str!("Paste"), [window_width * 0.5, 0.0]) {
+                        if let Some(text) = window_proxy.clipboard_text() {
+                            self.easing_css.clear();
+                            self.easing_css.push_str(&text);
+                            import = true;
+                        }
+                    }
+                    if import {
+                        match CubicBezierEasing::parse_css(self.easing_css.to_str()) {
+                            Ok(easing) => {
+                                self.pending_commands.push(EditorCommand::SetEasing { easing });
+                                self.easing_status = None;
+                            },
+                            Err(error) => self.easing_status = Some(easing_error_message(&error)),
+                        }
+                    }
+                    if let Some(status) = &self.easing_status {
+                        ui.text(status);
+                    }
+                    ui.color_edit(imgui::im_str!("axes color"), &mut self.editor_config.easing_axes_color).flags(imgui::ImGuiColorEditFlags::NoSmallPreview).build();
+                },
+            }
+            ui.dummy([window_width, window_height * 0.02]);
+            ui.text("Overlays:");
+            ui.checkbox(imgui::im_str!("Bounding box"), &mut self.editor_config.show_bounding_box);
+            if self.editor_config.show_bounding_box {
+                ui.color_edit(imgui::im_str!("bounding box color"), &mut self.editor_config.bounding_box_color).flags(imgui::ImGuiColorEditFlags::NoSmallPreview).build();
+            }
+            ui.checkbox(imgui::im_str!("Extrema"), &mut self.editor_config.show_extrema);
+            if self.editor_config.show_extrema {
+                ui.color_edit(imgui::im_str!("extrema color"), &mut self.editor_config.extrema_color).flags(imgui::ImGuiColorEditFlags::NoSmallPreview).build();
+            }
+            ui.checkbox(imgui::im_str!("Convex hull"), &mut self.editor_config.show_convex_hull);
+            if self.editor_config.show_convex_hull {
+                ui.color_edit(imgui::im_str!("convex hull color"), &mut self.editor_config.convex_hull_color).flags(imgui::ImGuiColorEditFlags::NoSmallPreview).build();
+            }
+            ui.dummy([window_width, window_height * 0.02]);
+            ui.checkbox(imgui::im_str!("Curvature comb"), &mut self.editor_config.show_curvature_comb);
+            if self.editor_config.show_curvature_comb {
+                ui.text("Comb scale:");
+                ui.slider_float(imgui::im_str!("##comb scale"), &mut self.editor_config.curvature_comb_scale, 10.0, 20000.0).power(3.0).build();
+                ui.color_edit(imgui::im_str!("curvature comb color"), &mut self.editor_config.curvature_comb_color).flags(imgui::ImGuiColorEditFlags::NoSmallPreview).build();
+            }
+            ui.checkbox(imgui::im_str!("Curvature plot"), &mut self.editor_config.show_curvature_plot);
+            if self.editor_config.show_curvature_plot {
+                ui.text("Curvature over:");
+                ui.radio_button(imgui::im_str!("t"), &mut self.editor_config.curvature_plot_over_arc_length, false);
+                ui.same_line(0.0);
+                ui.radio_button(imgui::im_str!("Arc length"), &mut self.editor_config.curvature_plot_over_arc_length, true);
+                let profile = editor_layer.curvature_profile(&self.editor_config, self.editor_config.curvature_plot_over_arc_length);
+                let largest = profile.iter().fold(0.0f32, |largest, curvature| largest.max(curvature.abs()));
+                let overlay = imgui::im_str!("max |k| = {:.4} 1/px", largest);
+                ui.plot_lines(imgui::im_str!("##curvature"), &profile)
+                    .scale_min(-largest)
+                    .scale_max(largest)
+                    .overlay_text(&overlay)
+                    .graph_size([window_width, window_height * 0.1])
+                    .build();
+            }
+            ui.dummy([window_width, window_height * 0.02]);
+            ui.checkbox(imgui::im_str!("Tangent at LARP"), &mut self.editor_config.show_tangent);
+            ui.checkbox(imgui::im_str!("Motion preview"), &mut self.editor_config.show_motion_preview);
+            if self.editor_config.show_motion_preview {
+                ui.text("Sprite size [px]:");
+                ui.slider_float(imgui::im_str!("##sprite size"), &mut self.editor_config.motion_sprite_size, 8.0, 80.0).build();
+                ui.color_edit(imgui::im_str!("sprite color"), &mut self.editor_config.motion_sprite_color).flags(imgui::ImGuiColorEditFlags::NoSmallPreview).build();
+                ui.checkbox(imgui::im_str!("Frenet frame"), &mut self.editor_config.show_frenet_frame);
+                if self.editor_config.show_frenet_frame {
+                    ui.text("Tangent and normal colors:");
+                    ui.color_edit(imgui::im_str!("tangent color"), &mut self.editor_config.tangent_color).flags(imgui::ImGuiColorEditFlags::NoSmallPreview).build();
+                    ui.color_edit(imgui::im_str!("normal color"), &mut self.editor_config.normal_color).flags(imgui::ImGuiColorEditFlags::NoSmallPreview).build();
+                }
+                if let Some(frame) = editor_layer.motion_frame(&self.editor_config) {
+                    // Screen y points down, the heading is counter-clockwise from the x axis as seen on screen.
+                    let heading = (-frame.tangent.1).atan2(frame.tangent.0).to_degrees();
+                    ui.text(&imgui::im_str!("Heading: {:.1} deg", heading));
+                }
+            }
+            ui.checkbox(imgui::im_str!("Hodograph"), &mut self.editor_config.show_hodograph);
+            if self.editor_config.show_hodograph {
+                ui.text("C' blue, C'' orange");
+                let at_larp = editor_layer.derivatives_at_larp(&self.editor_config);
+                hodograph_inset(&ui, &editor_layer.hodograph(&self.editor_config), at_larp, [window_width, window_width * 0.75]);
+            }
+            ui.dummy([window_width, window_height * 0.02]);
+            ui.checkbox(imgui::im_str!("Stroke outline"), &mut self.editor_config.show_stroke);
+            if self.editor_config.show_stroke {
+                ui.text("Width [px]:");
+                ui.slider_float(imgui::im_str!("##stroke width"), &mut self.editor_config.stroke_width, 1.0, 200.0).build();
+                ui.text("Caps:");
+                ui.radio_button(imgui::im_str!("Butt"), &mut self.editor_config.stroke_cap, LineCap::Butt);
+                ui.same_line(0.0);
+                ui.radio_button(imgui::im_str!("Round##cap"), &mut self.editor_config.stroke_cap, LineCap::Round);
+                ui.same_line(0.0);
+                ui.radio_button(imgui::im_str!("Square"), &mut self.editor_config.stroke_cap, LineCap::Square);
+                ui.text("Joins:");
+                ui.radio_button(imgui::im_str!("Miter"), &mut self.editor_config.stroke_join, LineJoin::Miter);
+                ui.same_line(0.0);
+                ui.radio_button(imgui::im_str!("Round##join"), &mut self.editor_config.stroke_join, LineJoin::Round);
+                ui.same_line(0.0);
+                ui.radio_button(imgui::im_str!("Bevel"), &mut self.editor_config.stroke_join, LineJoin::Bevel);
+                if self.editor_config.stroke_join == LineJoin::Miter {
+                    ui.text("Miter limit:");
+                    ui.slider_float(imgui::im_str!("##miter limit"), &mut self.editor_config.miter_limit, 1.0, 10.0).build();
+                }
+                ui.text("Offset tolerance [px]:");
+                ui.slider_float(imgui::im_str!("##offset tolerance"), &mut self.editor_config.offset_tolerance, 0.01, 5.0).power(3.0).build();
+                ui.text(&imgui::im_str!("Outline segments: {}", editor_layer.stroke_outline(&self.editor_config).len()));
+                ui.color_edit(imgui::im_str!("stroke color"), &mut self.editor_config.stroke_color).flags(imgui::ImGuiColorEditFlags::NoSmallPreview).build();
+                ui.text("Export to:");
+                ui.input_text(imgui::im_str!("##export path"), &mut self.export_path).resize_buffer(true).build();
+                ui.checkbox(imgui::im_str!("Overwrite existing file"), &mut self.overwrite_export);
+                if ui.button(imgui::im_str!("Export SVG"), [window_width, 0.0]) {
+                    self.pending_commands.push(EditorCommand::ExportStrokeOutline {
+                        path: self.export_path.to_str().to_string(),
+                        overwrite: self.overwrite_export,
+                    });
+                }
+                if let Some(status) = editor_layer.export_status() {
+                    ui.text(status);
+                }
+            }
+            ui.dummy([window_width, window_height * 0.02]);
+            ui.checkbox(imgui::im_str!("Show closest point"), &mut self.editor_config.show_closest_point);
+            if let Some(projection) = self.editor_config.show_closest_point.then(|| editor_layer.hover_projection(&self.editor_config)).flatten() {
+                ui.text(&imgui::im_str!("t = {:.4}", projection.t));
+                ui.text(&imgui::im_str!("Position: ({:.1}, {:.1}) px", projection.point.0, projection.point.1));
+                ui.text(&imgui::im_str!("Distance: {:.2} px", projection.distance));
+            }
+            ui.dummy([window_width, window_height * 0.02]);
+            ui.checkbox(imgui::im_str!("Show intersections"), &mut self.editor_config.show_intersections);
+            if self.editor_config.show_intersections {
+                let line_label = if editor_layer.has_intersection_line() { imgui::im_str!("Remove line") } else { imgui::im_str!("Add line") };
+                if ui.button(line_label, [window_width, 0.0]) {
+                    self.pending_commands.push(EditorCommand::ToggleIntersectionLine);
+                }
+                ui.color_edit(imgui::im_str!("intersection color"), &mut self.editor_config.intersection_color).flags(imgui::ImGuiColorEditFlags::NoSmallPreview).build();
+                for found in editor_layer.intersections(&self.editor_config).iter() {
+                    ui.text(&imgui::im_str!(
+                        "{} x {}: t1 = {:.3}, t2 = {:.3}",
+                        scene_curve_name(found.first), scene_curve_name(found.second), found.t1, found.t2
+                    ));
+                }
+            }
+            ui.dummy([window_width, window_height * 0.02]);
+            ui.checkbox(imgui::im_str!("Geometry checks"), &mut self.editor_config.show_geometry_checks);
+            if self.editor_config.show_geometry_checks {
+                let features = editor_layer.curve_features(&self.editor_config);
+                for found in &features.self_intersections {
+                    ui.text(&imgui::im_str!("Self-intersection: t1 = {:.3}, t2 = {:.3}", found.t1, found.t2));
+                }
+                for (t, _) in &features.cusps {
+                    ui.text(&imgui::im_str!("Cusp: t = {:.3}", t));
+                }
+                for (t, _) in &features.inflections {
+                    ui.text(&imgui::im_str!("Inflection: t = {:.3}", t));
+                }
+            }
+            ui.checkbox(imgui::im_str!("Mark checks on canvas"), &mut self.editor_config.mark_curve_features);
+            if self.editor_config.mark_curve_features {
+                ui.color_edit(imgui::im_str!("cusp color"), &mut self.editor_config.cusp_color).flags(imgui::ImGuiColorEditFlags::NoSmallPreview).build();
+                ui.color_edit(imgui::im_str!("inflection color"), &mut self.editor_config.inflection_color).flags(imgui::ImGuiColorEditFlags::NoSmallPreview).build();
+            }
         });
+        self.was_dragging_value = is_dragging_value;
         self.imgui_renderer.render(ui);
     }
-}
\ No newline at end of file
+}
+
+fn easing_error_message(error: &EasingParseError) -> String {
+    match error {
+        EasingParseError::UnknownFunction => String::from("Expected cubic-bezier(x1, y1, x2, y2) or a named easing."),
+        EasingParseError::WrongArgumentCount(count) => format!("Expected 4 numbers, found {}.", count),
+        EasingParseError::InvalidNumber(text) => format!("Not a number: \"{}\".", text),
+        EasingParseError::XOutOfRange => String::from("x1 and x2 must lie within [0, 1]."),
+    }
+}
+
+/// Draws a dot moving along a track by the easing of `progress`, next to a smaller dot moving linearly.
+fn easing_preview(ui: &imgui::Ui, easing: &CubicBezierEasing, progress: f32, size: [f32; 2]) {
+    let origin = ui.get_cursor_screen_pos();
+    // Easings may overshoot, the track leaves room for that on both sides.
+    let margin = 0.2 * size[0];
+    let (start, length) = (origin[0] + margin, size[0] - 2.0 * margin);
+    let (y, radius) = (origin[1] + 0.5 * size[1], 0.3 * size[1]);
+    let overshoot = margin / length;
+    let eased = easing.y_for_x(progress).clamp(-overshoot, 1.0 + overshoot);
+
+    let draw_list = ui.get_window_draw_list();
+    draw_list.add_rect(origin, [origin[0] + size[0], origin[1] + size[1]], [0.4, 0.4, 0.4]).build();
+    draw_list.add_line([start, y], [start + length, y], [0.6, 0.6, 0.6]).build();
+    for x in [start, start + length] {
+        draw_list.add_line([x, y - radius], [x, y + radius], [0.6, 0.6, 0.6]).build();
+    }
+    draw_list.add_circle([start + progress * length, y], 0.5 * radius, [0.5, 0.5, 0.5]).filled(true).build();
+    draw_list.add_circle([start + eased * length, y], radius, [0.3, 0.9, 0.3]).filled(true).build();
+    ui.dummy(size);
+}
+
+fn scene_curve_name(curve: SceneCurve) -> String {
+    match curve {
+        SceneCurve::Edited => String::from("edited"),
+        SceneCurve::Inactive(idx) => format!("curve {}", idx + 1),
+        SceneCurve::Line => String::from("line"),
+    }
+}
+
+/// Draws the derivative curves around the origin, scaled to fit `size`, with the derivatives at the larp point as lines.
+fn hodograph_inset(ui: &imgui::Ui, hodograph: &Hodograph, at_larp: Option<((f32, f32), (f32, f32))>, size: [f32; 2]) {
+    let origin = ui.get_cursor_screen_pos();
+    let control_points = hodograph.first_control_points.iter().chain(&hodograph.second_control_points).flatten();
+    let all_points = hodograph.first.iter().chain(&hodograph.second).chain(control_points).chain(std::iter::once(&(0.0, 0.0)));
+    let (min, max) = all_points.fold(((0.0f32, 0.0f32), (0.0f32, 0.0f32)), |(min, max), point| {
+        ((min.0.min(point.0), min.1.min(point.1)), (max.0.max(point.0), max.1.max(point.1)))
+    });
+    let scale = 0.9 * (size[0] / (max.0 - min.0).max(f32::EPSILON)).min(size[1] / (max.1 - min.1).max(f32::EPSILON));
+    let center = (0.5 * (min.0 + max.0), 0.5 * (min.1 + max.1));
+    let to_inset = |point: (f32, f32)| [
+        origin[0] + 0.5 * size[0] + (point.0 - center.0) * scale,
+        origin[1] + 0.5 * size[1] + (point.1 - center.1) * scale,
+    ];
+
+    let draw_list = ui.get_window_draw_list();
+    draw_list.add_rect(origin, [origin[0] + size[0], origin[1] + size[1]], [0.4, 0.4, 0.4]).build();
+    let zero = to_inset((0.0, 0.0));
+    draw_list.add_line([origin[0], zero[1]], [origin[0] + size[0], zero[1]], [0.3, 0.3, 0.3]).build();
+    draw_list.add_line([zero[0], origin[1]], [zero[0], origin[1] + size[1]], [0.3, 0.3, 0.3]).build();
+    let curves = [
+        (&hodograph.first, &hodograph.first_control_points, [0.3, 0.6, 1.0]),
+        (&hodograph.second, &hodograph.second_control_points, [1.0, 0.6, 0.2]),
+    ];
+    for (samples, polygons, color) in curves {
+        for polygon in polygons {
+            for pair in polygon.windows(2) {
+                draw_list.add_line(to_inset(pair[0]), to_inset(pair[1]), [0.6, 0.6, 0.6]).build();
+            }
+            for point in polygon {
+                draw_list.add_circle(to_inset(*point), 2.5, [0.8, 0.8, 0.3]).filled(true).build();
+            }
+        }
+        for pair in samples.windows(2) {
+            draw_list.add_line(to_inset(pair[0]), to_inset(pair[1]), color).thickness(2.0).build();
+        }
+    }
+    if let Some((first, second)) = at_larp {
+        draw_list.add_line(zero, to_inset(first), [0.3, 0.6, 1.0]).build();
+        draw_list.add_line(zero, to_inset(second), [1.0, 0.6, 0.2]).build();
+    }
+    ui.dummy(size);
+}
+
+/// Queues an edit made by dragging a widget. A drag that was not going on in the last frame starts
+/// a new edit session, so that all edits of one drag become one undo step.
+fn push_drag_edit(commands: &mut Vec<EditorCommand>, command: EditorCommand, was_dragging: bool) {
+    if !was_dragging {
+        commands.push(EditorCommand::BeginEdit);
+    }
+    commands.push(command);
+}
+
+/// Lists the control point weights with a readout of the hovered point.
+/// Returns the index and new value of the weight being edited, and whether any weight is being dragged.
+fn weight_sliders(ui: &imgui::Ui, editor_layer: &EditorLayer) -> (Option<(usize, f32)>, bool) {
+    let weights = editor_layer.control_point_weights();
+    ui.text("Control point weights:");
+    if let Some(index) = editor_layer.hovered_control_point() {
+        ui.text(format!("Hovered: P{} (w = {:.3})", index, weights[index]));
+    }
+
+    let mut edited = None;
+    let mut is_active = false;
+    for (index, weight) in weights.iter().enumerate() {
+        let mut weight = *weight;
+        if ui.drag_float(&imgui::im_str!("##weight{}", index), &mut weight)
+            .min(MIN_CONTROL_POINT_WEIGHT)
+            .max(MAX_CONTROL_POINT_WEIGHT)
+            .speed(0.01)
+            .display_format(&imgui::im_str!("P{}: %.3f", index))
+            .build() {
+            edited = Some((index, weight));
+        }
+        is_active |= ui.is_item_active();
+    }
+    (edited, is_active)
+}
+
+/// Draws the knots in `[0, 1]` as ticks along a line of the given size and lets the user drag them.
+/// Returns the index and new value of the knot being dragged, and whether a knot is held.
+fn knot_ticks(ui: &imgui::Ui, knots: &[f32], size: [f32; 2]) -> (Option<(usize, f32)>, bool) {
+    let origin = ui.get_cursor_screen_pos();
+    let tick_half_width = 4.0;
+    let mut dragged = None;
+    for (index, knot) in knots.iter().enumerate() {
+        ui.set_cursor_screen_pos([origin[0] + knot * size[0] - tick_half_width, origin[1]]);
+        ui.push_id(index as i32);
+        ui.invisible_button(imgui::im_str!("knot"), [2.0 * tick_half_width, size[1]]);
+        ui.pop_id();
+        if ui.is_item_active() {
+            let mouse_x = ui.io().mouse_pos[0];
+            dragged = Some((index, ((mouse_x - origin[0]) / size[0]).clamp(0.0, 1.0)));
+        }
+    }
+
+    let draw_list = ui.get_window_draw_list();
+    let baseline = origin[1] + 0.5 * size[1];
+    draw_list.add_line([origin[0], baseline], [origin[0] + size[0], baseline], [0.6, 0.6, 0.6]).build();
+    for knot in knots {
+        let x = origin[0] + knot * size[0];
+        draw_list.add_line([x, origin[1]], [x, origin[1] + size[1]], [0.9, 0.9, 0.3]).thickness(2.0).build();
+    }
+    ui.set_cursor_screen_pos([origin[0], origin[1] + size[1]]);
+    ui.dummy([size[0], 0.0]);
+    (dragged, dragged.is_some())
+}
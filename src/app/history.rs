@@ -0,0 +1,201 @@
+/// Oldest entries are dropped beyond this many.
+const MAX_ENTRIES: usize = 200;
+
+/// Edit that can be undone, kept as the state before and after it.
+#[derive(Debug, Clone)]
+struct Entry<T> {
+    label: String,
+    before: T,
+    after: T,
+    /// Edit session, e.g. one slider drag, whose later edits with the same label are folded into this one.
+    session: Option<u64>,
+}
+
+/// Undo and redo stack of edits. The entries before the position are applied, the ones after
+/// it were undone and are dropped by the next edit.
+#[derive(Debug, Clone)]
+pub struct History<T> {
+    entries: Vec<Entry<T>>,
+    position: usize,
+}
+
+impl<T: Clone + PartialEq> History<T> {
+    pub fn new() -> Self {
+        Self { entries: Vec::new(), position: 0 }
+    }
+
+    /// Records an edit from `before` to `after`. Edits that change nothing are ignored.
+    pub fn record(&mut self, label: String, before: T, after: T) {
+        self.push(Entry { label, before, after, session: None });
+    }
+
+    /// Records an edit that merges with the previous one if that has the same label and belongs to the
+    /// same `session`, e.g. the frames of one slider drag. The caller starts a new session per gesture.
+    pub fn record_merging(&mut self, label: String, session: u64, before: T, after: T) {
+        let is_continued = self.position == self.entries.len() &&
+            self.entries.last().is_some_and(|last| last.session == Some(session) && last.label == label);
+        if is_continued && before != after {
+            if let Some(last) = self.entries.last_mut() {
+                last.after = after;
+            }
+            return;
+        }
+        self.push(Entry { label, before, after, session: Some(session) });
+    }
+
+    fn push(&mut self, entry: Entry<T>) {
+        if entry.before == entry.after {
+            return;
+        }
+        self.entries.truncate(self.position);
+        self.entries.push(entry);
+        if self.entries.len() > MAX_ENTRIES {
+            self.entries.remove(0);
+        }
+        self.position = self.entries.len();
+    }
+
+    /// Labels of all entries, applied and undone.
+    pub fn labels(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().map(|entry| entry.label.as_str())
+    }
+
+    /// Number of applied entries.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Steps back one entry and returns the state to restore.
+    pub fn undo(&mut self) -> Option<&T> {
+        match self.position.checked_sub(1) {
+            Some(position) => self.jump_to(position),
+            None => None,
+        }
+    }
+
+    /// Steps forward one entry and returns the state to restore.
+    pub fn redo(&mut self) -> Option<&T> {
+        self.jump_to(self.position + 1)
+    }
+
+    /// Every recorded state, e.g. to convert them all when their coordinates change meaning.
+    pub fn states_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.entries.iter_mut().flat_map(|entry| [&mut entry.before, &mut entry.after])
+    }
+
+    /// Moves to the state after the first `position` entries and returns it, 0 is the state before any of them.
+    pub fn jump_to(&mut self, position: usize) -> Option<&T> {
+        if position > self.entries.len() || position == self.position {
+            return None;
+        }
+        self.position = position;
+        match position {
+            0 => self.entries.first().map(|entry| &entry.before),
+            _ => Some(&self.entries[position - 1].after),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history_of(count: i32) -> History<i32> {
+        let mut history = History::new();
+        for value in 0..count {
+            history.record(format!("Set {}", value + 1), value, value + 1);
+        }
+        history
+    }
+
+    #[test]
+    fn undo_and_redo_step_through_the_states() {
+        let mut history = history_of(3);
+        assert_eq!(history.position(), 3);
+        assert_eq!(history.undo(), Some(&2));
+        assert_eq!(history.undo(), Some(&1));
+        assert_eq!(history.redo(), Some(&2));
+        assert_eq!(history.redo(), Some(&3));
+        assert_eq!(history.redo(), None);
+        assert_eq!(history.position(), 3);
+
+        let mut empty: History<i32> = History::new();
+        assert_eq!(empty.undo(), None);
+        assert_eq!(empty.redo(), None);
+    }
+
+    #[test]
+    fn converted_states_are_restored() {
+        let mut history = history_of(2);
+        history.states_mut().for_each(|state| *state *= 10);
+        assert_eq!(history.undo(), Some(&10));
+        assert_eq!(history.undo(), Some(&0));
+        assert_eq!(history.redo(), Some(&10));
+        assert_eq!(history.redo(), Some(&20));
+    }
+
+    #[test]
+    fn new_edit_drops_the_undone_entries() {
+        let mut history = history_of(3);
+        history.undo();
+        history.undo();
+        history.record(String::from("Other"), 1, 10);
+        assert_eq!(history.labels().collect::<Vec<_>>(), vec!["Set 1", "Other"]);
+        assert_eq!(history.redo(), None);
+        assert_eq!(history.undo(), Some(&1));
+    }
+
+    #[test]
+    fn jump_to_goes_both_ways() {
+        let mut history = history_of(4);
+        assert_eq!(history.jump_to(0), Some(&0));
+        assert_eq!(history.position(), 0);
+        assert_eq!(history.jump_to(3), Some(&3));
+        assert_eq!(history.jump_to(1), Some(&1));
+        assert_eq!(history.jump_to(1), None);
+        assert_eq!(history.jump_to(5), None);
+        assert_eq!(history.position(), 1);
+    }
+
+    #[test]
+    fn oldest_entries_are_evicted() {
+        let mut history = history_of(MAX_ENTRIES as i32 + 5);
+        assert_eq!(history.labels().count(), MAX_ENTRIES);
+        assert_eq!(history.labels().next(), Some("Set 6"));
+        assert_eq!(history.jump_to(0), Some(&5));
+    }
+
+    #[test]
+    fn edits_without_change_are_skipped() {
+        let mut history = history_of(1);
+        history.record(String::from("Nothing"), 1, 1);
+        history.record_merging(String::from("Nothing"), 7, 1, 1);
+        assert_eq!(history.labels().count(), 1);
+        assert_eq!(history.position(), 1);
+    }
+
+    #[test]
+    fn merging_stops_at_a_session_boundary() {
+        let mut history = History::new();
+        history.record_merging(String::from("Weight"), 1, 0, 1);
+        history.record_merging(String::from("Weight"), 1, 1, 2);
+        history.record_merging(String::from("Weight"), 1, 2, 3);
+        assert_eq!(history.labels().count(), 1);
+
+        history.record_merging(String::from("Weight"), 2, 3, 4);
+        history.record_merging(String::from("Knot"), 2, 4, 5);
+        history.record(String::from("Weight"), 5, 6);
+        history.record_merging(String::from("Weight"), 2, 6, 7);
+        assert_eq!(history.labels().count(), 5);
+        assert_eq!(history.jump_to(1), Some(&3));
+        assert_eq!(history.jump_to(0), Some(&0));
+
+        // An undone merged entry is not continued.
+        let mut history = History::new();
+        history.record_merging(String::from("Weight"), 1, 0, 1);
+        history.undo();
+        history.record_merging(String::from("Weight"), 1, 0, 2);
+        assert_eq!(history.redo(), None);
+        assert_eq!(history.undo(), Some(&0));
+    }
+}
@@ -2,6 +2,9 @@ pub mod application;
 mod window_proxy;
 mod editor_config;
 mod application_event;
+mod editor_command;
 mod gui_layer;
 mod gl_renderer;
-mod editor_layer;
\ No newline at end of file
+mod editor_layer;
+mod animation;
+mod history;
@@ -57,4 +57,12 @@ impl<'a> Window<'a> {
         let (w, h) = self.window_ref.get_size();
         (w as u32, h as u32)
     }
+
+    pub fn clipboard_text(&self) -> Option<String> {
+        self.window_ref.get_clipboard_string()
+    }
+
+    pub fn set_clipboard_text(&mut self, text: &str) {
+        self.window_ref.set_clipboard_string(text)
+    }
 }
\ No newline at end of file
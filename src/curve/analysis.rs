@@ -0,0 +1,222 @@
+use crate::curve::bezier::BezierCurve;
+use crate::curve::intersection::{self, Intersection};
+use crate::curve::roots;
+
+/// Number of intervals probed for sign changes and minima.
+const SAMPLES: usize = 256;
+/// A local minimum of the speed below this fraction of the top speed counts as a cusp.
+const CUSP_SPEED_RATIO: f32 = 1e-3;
+
+fn speed(curve: &BezierCurve, t: f32) -> f32 {
+    curve.derivatives(t).map_or(0.0, |[_, d1, _]| (d1.0 * d1.0 + d1.1 * d1.1).sqrt())
+}
+
+/// Parameters in `(0, 1)` at which the curve stops and turns back, i.e. its speed drops to zero.
+pub fn cusps(curve: &BezierCurve) -> Vec<f32> {
+    if curve.degree() < 2 {
+        return Vec::new();
+    }
+
+    let speeds: Vec<f32> = (0..=SAMPLES).map(|step| speed(curve, step as f32 / SAMPLES as f32)).collect();
+    let top_speed = speeds.iter().copied().fold(0.0, f32::max);
+    let mut cusps: Vec<f32> = Vec::new();
+    for idx in 1..SAMPLES {
+        if speeds[idx] > speeds[idx - 1] || speeds[idx] > speeds[idx + 1] {
+            continue;
+        }
+        let t = roots::minimize(|t| speed(curve, t), (idx - 1) as f32 / SAMPLES as f32, (idx + 1) as f32 / SAMPLES as f32);
+        let is_known = cusps.last().is_some_and(|last| (t - last).abs() < 1.0 / SAMPLES as f32);
+        if speed(curve, t) <= CUSP_SPEED_RATIO * top_speed && !is_known {
+            cusps.push(t);
+        }
+    }
+    cusps
+}
+
+/// Parameters in `(0, 1)` at which the curve changes its turning direction. Cusps are not included.
+pub fn inflections(curve: &BezierCurve) -> Vec<f32> {
+    if curve.degree() < 2 {
+        return Vec::new();
+    }
+
+    let cross = |t: f32| curve.derivatives(t).map_or(0.0, |[_, d1, d2]| d1.0 * d2.1 - d1.1 * d2.0);
+    let cusps = cusps(curve);
+    roots::roots(cross, 0.0, 1.0, SAMPLES).into_iter()
+        .filter(|t| *t > 0.0 && *t < 1.0)
+        .filter(|t| cusps.iter().all(|cusp| (t - cusp).abs() > 1.0 / SAMPLES as f32))
+        .collect()
+}
+
+/// Parameters in `(0, 1)` at which the x and at which the y coordinate of the curve are extremal,
+/// found as roots of the derivative.
+pub fn extrema(curve: &BezierCurve) -> (Vec<f32>, Vec<f32>) {
+    let interior = |roots: Vec<f32>| roots.into_iter().filter(|t| *t > 0.0 && *t < 1.0).collect();
+    let derivative = |t: f32| curve.derivatives(t).map_or((0.0, 0.0), |[_, d1, _]| d1);
+    (
+        interior(roots::roots(|t| derivative(t).0, 0.0, 1.0, SAMPLES)),
+        interior(roots::roots(|t| derivative(t).1, 0.0, 1.0, SAMPLES)),
+    )
+}
+
+/// Smallest axis-aligned box containing the curve, as its minimum and maximum corner. Unlike the box
+/// around the control points it touches the curve on every side.
+pub fn bounding_box(curve: &BezierCurve) -> Option<((f32, f32), (f32, f32))> {
+    let (x_extrema, y_extrema) = extrema(curve);
+    [0.0, 1.0].into_iter()
+        .chain(x_extrema)
+        .chain(y_extrema)
+        .filter_map(|t| curve.evaluate(t))
+        .fold(None, |bounds, point| match bounds {
+            None => Some((point, point)),
+            Some((min, max)) => Some(((min.0.min(point.0), min.1.min(point.1)), (max.0.max(point.0), max.1.max(point.1)))),
+        })
+}
+
+/// Convex hull of `points` in counter-clockwise order, using the monotone chain algorithm.
+/// Collinear points on the hull boundary are left out.
+pub fn convex_hull(points: &[(f32, f32)]) -> Vec<(f32, f32)> {
+    let mut sorted = points.to_vec();
+    sorted.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.total_cmp(&b.1)));
+    sorted.dedup();
+    if sorted.len() < 3 {
+        return sorted;
+    }
+
+    let cross = |o: (f32, f32), a: (f32, f32), b: (f32, f32)| (a.0 - o.0) * (b.1 - o.1) - (a.1 - o.1) * (b.0 - o.0);
+    let mut hull: Vec<(f32, f32)> = Vec::with_capacity(2 * sorted.len());
+    for pass in [sorted.clone(), sorted.into_iter().rev().collect()] {
+        let chain_start = hull.len();
+        for point in pass {
+            while hull.len() >= chain_start + 2 && cross(hull[hull.len() - 2], hull[hull.len() - 1], point) <= 0.0 {
+                hull.pop();
+            }
+            hull.push(point);
+        }
+        // The last point of each chain starts the other one.
+        hull.pop();
+    }
+    hull
+}
+
+/// Points where a curve, given as consecutive segments with the range of the curve parameter each one
+/// covers, crosses itself. Every intersection is reported once with `t1 < t2`, within `tolerance`.
+pub fn self_intersections(segments: &[(BezierCurve, (f32, f32))], tolerance: f32) -> Vec<Intersection> {
+    // A piece along which x only grows or only shrinks cannot cross itself.
+    let mut pieces = Vec::new();
+    for (segment, range) in segments {
+        let derivative_x = |t: f32| segment.derivatives(t).map_or(0.0, |[_, d1, _]| d1.0);
+        let mut splits: Vec<f32> = roots::roots(derivative_x, 0.0, 1.0, SAMPLES).into_iter()
+            .filter(|t| *t > 0.0 && *t < 1.0)
+            .collect();
+        splits.push(1.0);
+        let mut start = 0.0;
+        for end in splits {
+            let piece = segment.split(start).1.split((end - start) / (1.0 - start)).0;
+            let to_curve_parameter = |s: f32| range.0 + (start + s * (end - start)) * (range.1 - range.0);
+            pieces.push((piece, to_curve_parameter(0.0), to_curve_parameter(1.0)));
+            start = end;
+        }
+    }
+
+    let mut found: Vec<Intersection> = Vec::new();
+    for (first_idx, (first, first_start, first_end)) in pieces.iter().enumerate() {
+        for (second, second_start, second_end) in pieces.iter().skip(first_idx + 1) {
+            for candidate in intersection::curve_curve(first, second, tolerance) {
+                let t1 = first_start + candidate.t1 * (first_end - first_start);
+                let t2 = second_start + candidate.t2 * (second_end - second_start);
+                let is_known = found.iter().any(|known| {
+                    (known.point.0 - candidate.point.0).abs() <= 4.0 * tolerance &&
+                    (known.point.1 - candidate.point.1).abs() <= 4.0 * tolerance
+                });
+                if !is_known && !is_touching(segments, t1, t2, candidate.point, tolerance) {
+                    found.push(Intersection { t1, t2, point: candidate.point });
+                }
+            }
+        }
+    }
+    found.sort_by(|first, second| first.t1.total_cmp(&second.t1));
+    found
+}
+
+/// Whether the curve between `t1` and `t2` stays at `point`, which is where neighbouring pieces
+/// meet rather than a loop closing.
+fn is_touching(segments: &[(BezierCurve, (f32, f32))], t1: f32, t2: f32, point: (f32, f32), tolerance: f32) -> bool {
+    (0..=8).all(|step| {
+        let t = t1 + (t2 - t1) * step as f32 / 8.0;
+        let segment = segments.iter()
+            .find(|(_, range)| t >= range.0 && t <= range.1)
+            .or(segments.last());
+        match segment.and_then(|(curve, range)| curve.evaluate((t - range.0) / (range.1 - range.0))) {
+            Some(on_curve) => ((on_curve.0 - point.0).powi(2) + (on_curve.1 - point.1).powi(2)).sqrt() <= 4.0 * tolerance,
+            None => true,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: (f32, f32), b: (f32, f32)) {
+        assert!((a.0 - b.0).abs() < 1e-2 && (a.1 - b.1).abs() < 1e-2, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn symmetric_cusp() {
+        let curve = BezierCurve::new(vec![(0.0, 0.0), (1.0, 1.0), (0.0, 1.0), (1.0, 0.0)]);
+        let cusps = cusps(&curve);
+        assert_eq!(cusps.len(), 1);
+        assert!((cusps[0] - 0.5).abs() < 1e-3);
+        assert!(inflections(&curve).is_empty());
+        assert!(self_intersections(&[(curve, (0.0, 1.0))], 1e-3).is_empty());
+    }
+
+    #[test]
+    fn s_curve_inflects_in_the_middle() {
+        let curve = BezierCurve::new(vec![(0.0, 0.0), (1.0, 1.0), (2.0, -1.0), (3.0, 0.0)]);
+        let inflections = inflections(&curve);
+        assert_eq!(inflections.len(), 1);
+        assert!((inflections[0] - 0.5).abs() < 1e-3);
+        assert!(cusps(&curve).is_empty());
+    }
+
+    #[test]
+    fn loop_crosses_itself_once() {
+        let curve = BezierCurve::new(vec![(0.0, 0.0), (300.0, 200.0), (-100.0, 200.0), (200.0, 0.0)]);
+        let found = self_intersections(&[(curve.clone(), (0.0, 1.0))], 1e-2);
+        assert_eq!(found.len(), 1);
+        assert!(found[0].t1 < found[0].t2);
+        assert_close(curve.evaluate(found[0].t1).unwrap(), curve.evaluate(found[0].t2).unwrap());
+        assert!(cusps(&curve).is_empty());
+    }
+
+    #[test]
+    fn crossing_between_segments() {
+        let first = BezierCurve::new(vec![(0.0, 0.0), (100.0, 0.0), (100.0, 100.0)]);
+        let second = BezierCurve::new(vec![(100.0, 100.0), (100.0, 200.0), (50.0, -50.0)]);
+        let found = self_intersections(&[(first, (0.0, 0.5)), (second, (0.5, 1.0))], 1e-2);
+        assert_eq!(found.len(), 1);
+        assert!(found[0].t1 < 0.5 && found[0].t2 > 0.5);
+    }
+
+    #[test]
+    fn bounding_box_touches_the_curve() {
+        let curve = BezierCurve::new(vec![(0.0, 0.0), (1.0, 2.0), (2.0, -2.0), (3.0, 0.0)]);
+        let ((min_x, min_y), (max_x, max_y)) = bounding_box(&curve).unwrap();
+        assert_eq!((min_x, max_x), (0.0, 3.0));
+        // The curve is point symmetric, its peak is at t = (3 - sqrt(3)) / 6 with a height of sqrt(3) / 3.
+        assert!((max_y - 3.0f32.sqrt() / 3.0).abs() < 1e-4);
+        assert!((min_y + 3.0f32.sqrt() / 3.0).abs() < 1e-4);
+        let (x_extrema, y_extrema) = extrema(&curve);
+        assert!(x_extrema.is_empty());
+        assert_eq!(y_extrema.len(), 2);
+        assert!((y_extrema[0] - (3.0 - 3.0f32.sqrt()) / 6.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn convex_hull_drops_inner_points() {
+        let points = [(0.0, 0.0), (1.0, 1.0), (2.0, 0.0), (2.0, 2.0), (0.0, 2.0), (1.0, 2.0), (0.5, 1.5)];
+        assert_eq!(convex_hull(&points), vec![(0.0, 0.0), (2.0, 0.0), (2.0, 2.0), (0.0, 2.0)]);
+        assert_eq!(convex_hull(&points[..2]), vec![(0.0, 0.0), (1.0, 1.0)]);
+    }
+}
@@ -0,0 +1,101 @@
+/// Table mapping curve parameters to the distance travelled along the curve,
+/// built from a polyline through evenly spaced parameters.
+///
+/// The curve is given as an evaluation function over `[0, 1]`, so the same table
+/// works for every kind of curve in this crate.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArcLengthTable {
+    lengths: Vec<f32>,
+}
+
+impl ArcLengthTable {
+    /// Samples the curve at `samples + 1` evenly spaced parameters. Parameters
+    /// at which the curve cannot be evaluated add no length.
+    pub fn new<F>(samples: usize, evaluate: F) -> Self
+        where F: Fn(f32) -> Option<(f32, f32)> {
+        let samples = samples.max(1);
+        let mut lengths = Vec::with_capacity(samples + 1);
+        let mut length = 0.0;
+        let mut previous = evaluate(0.0);
+        lengths.push(length);
+        for step in 1..=samples {
+            let point = evaluate(step as f32 / samples as f32);
+            if let (Some(a), Some(b)) = (previous, point) {
+                length += ((b.0 - a.0).powi(2) + (b.1 - a.1).powi(2)).sqrt();
+            }
+            lengths.push(length);
+            previous = point.or(previous);
+        }
+        Self { lengths }
+    }
+
+    pub fn total_length(&self) -> f32 {
+        self.lengths[self.lengths.len() - 1]
+    }
+
+    /// Distance along the curve from its start to the parameter `t`.
+    pub fn length_at(&self, t: f32) -> f32 {
+        let scaled = t.clamp(0.0, 1.0) * (self.lengths.len() - 1) as f32;
+        let idx = (scaled.floor() as usize).min(self.lengths.len() - 2);
+        let fraction = scaled - idx as f32;
+        self.lengths[idx] + fraction * (self.lengths[idx + 1] - self.lengths[idx])
+    }
+
+    /// Parameter at which the distance along the curve reaches `length`.
+    pub fn parameter_at(&self, length: f32) -> f32 {
+        let length = length.clamp(0.0, self.total_length());
+        let idx = self.lengths.partition_point(|value| *value < length).clamp(1, self.lengths.len() - 1);
+        let (start, end) = (self.lengths[idx - 1], self.lengths[idx]);
+        let fraction = if end > start { (length - start) / (end - start) } else { 0.0 };
+        (idx as f32 - 1.0 + fraction) / (self.lengths.len() - 1) as f32
+    }
+
+    /// Parameter at which the given fraction of the total length is reached, so that
+    /// evenly spaced fractions give evenly spaced points along the curve.
+    pub fn parameter_at_fraction(&self, fraction: f32) -> f32 {
+        self.parameter_at(fraction * self.total_length())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curve::bezier::BezierCurve;
+
+    #[test]
+    fn line_length_is_exact() {
+        let table = ArcLengthTable::new(10, |t| Some((3.0 * t, 4.0 * t)));
+        assert!((table.total_length() - 5.0).abs() < 1e-5);
+        assert!((table.length_at(0.25) - 1.25).abs() < 1e-5);
+        assert!((table.parameter_at(2.5) - 0.5).abs() < 1e-5);
+    }
+
+    #[test]
+    fn quarter_circle_length() {
+        let curve = BezierCurve::quarter_circle((0.0, 0.0), 2.0);
+        let table = ArcLengthTable::new(512, |t| curve.evaluate(t));
+        assert!((table.total_length() - std::f32::consts::PI).abs() < 1e-3);
+    }
+
+    #[test]
+    fn fractions_give_evenly_spaced_points() {
+        // The middle control point is close to the start, so the curve is slow at first.
+        let curve = BezierCurve::new(vec![(0.0, 0.0), (0.1, 0.0), (4.0, 0.0)]);
+        let table = ArcLengthTable::new(512, |t| curve.evaluate(t));
+        for step in 0..=8 {
+            let fraction = step as f32 / 8.0;
+            let point = curve.evaluate(table.parameter_at_fraction(fraction)).unwrap();
+            assert!((point.0 - 4.0 * fraction).abs() < 1e-2, "{:?} at {}", point, fraction);
+        }
+    }
+
+    #[test]
+    fn parameter_and_length_are_inverse() {
+        let curve = BezierCurve::new(vec![(0.0, 0.0), (1.0, 2.0), (3.0, -1.0), (4.0, 0.5)]);
+        let table = ArcLengthTable::new(256, |t| curve.evaluate(t));
+        for step in 0..=10 {
+            let t = step as f32 / 10.0;
+            assert!((table.parameter_at(table.length_at(t)) - t).abs() < 1e-4);
+        }
+    }
+}
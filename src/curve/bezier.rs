@@ -0,0 +1,635 @@
+use crate::curve::evaluator::{self, BezierEvaluator, STACK_POINTS};
+use crate::curve::linalg;
+use crate::curve::roots;
+
+/// Number of intervals searched for candidates of the closest point.
+const PROJECTION_SAMPLES: usize = 128;
+
+/// Point on a curve closest to some query point.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Projection {
+    pub t: f32,
+    pub point: (f32, f32),
+    pub distance: f32,
+}
+
+/// Rational Bezier curve over 2D control points.
+///
+/// Every control point carries a weight. With all weights equal to one the
+/// curve is the ordinary polynomial Bezier curve, other weights allow exact
+/// conic sections such as circle arcs.
+///
+/// The curve does not care about the coordinate space of its points, so the
+/// editor can feed it normalized window coordinates while tools can use any
+/// other units.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BezierCurve {
+    control_points: Vec<(f32, f32)>,
+    weights: Vec<f32>,
+}
+
+impl BezierCurve {
+    /// Creates a polynomial curve, i.e. every control point has a weight of one.
+    pub fn new(control_points: Vec<(f32, f32)>) -> Self {
+        let weights = vec![1.0; control_points.len()];
+        Self { control_points, weights }
+    }
+
+    /// Creates a rational curve. Weights are expected to be positive and there
+    /// must be exactly one weight per control point.
+    pub fn rational(control_points: Vec<(f32, f32)>, weights: Vec<f32>) -> Self {
+        assert_eq!(control_points.len(), weights.len(), "every control point needs a weight");
+        Self { control_points, weights }
+    }
+
+    /// Exact quarter of the circle around `center`, going counter-clockwise
+    /// from `center + (radius, 0)` to `center + (0, radius)`.
+    pub fn quarter_circle(center: (f32, f32), radius: f32) -> Self {
+        Self::rational(
+            vec![
+                (center.0 + radius, center.1),
+                (center.0 + radius, center.1 + radius),
+                (center.0, center.1 + radius),
+            ],
+            vec![1.0, std::f32::consts::FRAC_1_SQRT_2, 1.0],
+        )
+    }
+
+    pub fn control_points(&self) -> &[(f32, f32)] {
+        &self.control_points
+    }
+
+    pub fn weights(&self) -> &[f32] {
+        &self.weights
+    }
+
+    pub fn is_rational(&self) -> bool {
+        self.weights.iter().any(|weight| *weight != self.weights[0])
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.control_points.is_empty()
+    }
+
+    /// Degree of the curve, which is one less than the number of control points.
+    /// An empty curve reports a degree of 0.
+    pub fn degree(&self) -> usize {
+        self.control_points.len().saturating_sub(1)
+    }
+
+    /// Evaluates the curve at `t` using the de Casteljau algorithm on the
+    /// homogeneous control points, in f64.
+    ///
+    /// Returns `None` for a curve without control points. A curve with a single
+    /// control point is that point for every `t`. Up to [`STACK_POINTS`] control points
+    /// nothing is allocated, for repeated evaluation of larger curves use a [`BezierEvaluator`].
+    pub fn evaluate(&self, t: f32) -> Option<(f32, f32)> {
+        let count = self.control_points.len();
+        if count > STACK_POINTS {
+            return BezierEvaluator::new(self).evaluate(t);
+        }
+
+        let mut buffer = [[0.0f64; 3]; STACK_POINTS];
+        for (slot, (point, weight)) in buffer.iter_mut().zip(self.control_points.iter().zip(&self.weights)) {
+            let weight = *weight as f64;
+            *slot = [point.0 as f64 * weight, point.1 as f64 * weight, weight];
+        }
+        evaluator::de_casteljau_in_place(&mut buffer[..count], t as f64).map(evaluator::to_point)
+    }
+
+    fn homogeneous(&self) -> Vec<[f64; 3]> {
+        self.control_points.iter()
+            .zip(&self.weights)
+            .map(|(point, weight)| {
+                let weight = *weight as f64;
+                [point.0 as f64 * weight, point.1 as f64 * weight, weight]
+            })
+            .collect()
+    }
+
+    fn from_homogeneous(points: impl IntoIterator<Item = [f64; 3]>) -> Self {
+        let (control_points, weights) = points.into_iter()
+            .map(|point| (evaluator::to_point(point), point[2] as f32))
+            .unzip();
+        Self { control_points, weights }
+    }
+
+    /// Position and first and second derivative at `t`. For rational curves the
+    /// derivatives follow from the quotient rule on the homogeneous curve. Like [`Self::evaluate`]
+    /// it works in f64 and nothing is allocated up to [`STACK_POINTS`] control points.
+    pub fn derivatives(&self, t: f32) -> Option<[(f32, f32); 3]> {
+        let count = self.control_points.len();
+        let mut stack_buffer = [[0.0f64; 3]; STACK_POINTS];
+        let mut heap_buffer = Vec::new();
+        let buffer = if count <= STACK_POINTS {
+            &mut stack_buffer[..count]
+        } else {
+            heap_buffer.resize(count, [0.0; 3]);
+            &mut heap_buffer[..]
+        };
+        for (slot, (point, weight)) in buffer.iter_mut().zip(self.control_points.iter().zip(&self.weights)) {
+            let weight = *weight as f64;
+            *slot = [point.0 as f64 * weight, point.1 as f64 * weight, weight];
+        }
+        let [position, d1, d2] = derivatives_in_place(buffer, t as f64)?;
+
+        let w = position[2];
+        let point = (position[0] / w, position[1] / w);
+        let first_derivative = ((d1[0] - d1[2] * point.0) / w, (d1[1] - d1[2] * point.1) / w);
+        let second_derivative = (
+            (d2[0] - 2.0 * d1[2] * first_derivative.0 - d2[2] * point.0) / w,
+            (d2[1] - 2.0 * d1[2] * first_derivative.1 - d2[2] * point.1) / w,
+        );
+        let as_f32 = |(x, y): (f64, f64)| (x as f32, y as f32);
+        Some([as_f32(point), as_f32(first_derivative), as_f32(second_derivative)])
+    }
+
+    /// Derivative of a polynomial curve as a Bezier curve of one degree less, whose control points
+    /// are `degree * (P[i + 1] - P[i])`. The derivative of a rational curve is not a Bezier curve
+    /// of lower degree, so rational curves return `None`.
+    pub fn hodograph(&self) -> Option<BezierCurve> {
+        if self.is_rational() || self.is_empty() {
+            return None;
+        }
+        if self.degree() == 0 {
+            return Some(Self::new(vec![(0.0, 0.0)]));
+        }
+
+        // All weights are equal, so they cancel out of the homogeneous differences.
+        let degree = self.degree() as f32;
+        Some(Self::new(self.control_points.windows(2)
+            .map(|pair| (degree * (pair[1].0 - pair[0].0), degree * (pair[1].1 - pair[0].1)))
+            .collect()))
+    }
+
+    /// Signed curvature at `t`, positive where the curve turns counter-clockwise.
+    /// Returns `None` where the curve has no tangent.
+    pub fn curvature(&self, t: f32) -> Option<f32> {
+        let [_, d1, d2] = self.derivatives(t)?;
+        let speed = (d1.0 * d1.0 + d1.1 * d1.1).sqrt();
+        if speed <= f32::EPSILON {
+            return None;
+        }
+        Some((d1.0 * d2.1 - d1.1 * d2.0) / speed.powi(3))
+    }
+
+    /// Unit tangent and unit normal of the Frenet frame at `t`. The normal points to the side the curve
+    /// turns to, where the curve is straight it is the tangent turned counter-clockwise.
+    /// Returns `None` where the curve has no tangent.
+    pub fn frenet_frame(&self, t: f32) -> Option<((f32, f32), (f32, f32))> {
+        let [_, d1, d2] = self.derivatives(t)?;
+        let speed = (d1.0 * d1.0 + d1.1 * d1.1).sqrt();
+        if speed <= f32::EPSILON {
+            return None;
+        }
+        let tangent = (d1.0 / speed, d1.1 / speed);
+        let normal = if d1.0 * d2.1 - d1.1 * d2.0 < 0.0 { (tangent.1, -tangent.0) } else { (-tangent.1, tangent.0) };
+        Some((tangent, normal))
+    }
+
+    /// Point of the curve closest to `point`.
+    ///
+    /// The candidates are both end points and the roots of `(C(t) - point) . C'(t)`, where the
+    /// direction to the query point is perpendicular to the curve. The roots are bracketed on a fine
+    /// grid and refined by bisection, so every local minimum of the distance is considered.
+    pub fn project(&self, point: (f32, f32)) -> Option<Projection> {
+        let projection = |t: f32| {
+            self.evaluate(t).map(|on_curve| Projection {
+                t,
+                point: on_curve,
+                distance: ((on_curve.0 - point.0).powi(2) + (on_curve.1 - point.1).powi(2)).sqrt(),
+            })
+        };
+        let perpendicularity = |t: f32| match self.derivatives(t) {
+            Some([on_curve, d1, _]) => (on_curve.0 - point.0) * d1.0 + (on_curve.1 - point.1) * d1.1,
+            None => 0.0,
+        };
+
+        roots::roots(perpendicularity, 0.0, 1.0, PROJECTION_SAMPLES).into_iter()
+            .chain([0.0, 1.0])
+            .filter_map(projection)
+            .min_by(|a, b| a.distance.total_cmp(&b.distance))
+    }
+
+    /// Splits the curve at `t` into the parts over `[0, t]` and `[t, 1]`, each again
+    /// reparameterized to `[0, 1]`. The control points are the outer edges of the
+    /// de Casteljau pyramid, so together the parts trace exactly the original curve.
+    pub fn split(&self, t: f32) -> (BezierCurve, BezierCurve) {
+        let mut buffer = self.homogeneous();
+        let mut left = Vec::with_capacity(buffer.len());
+        let mut right = Vec::with_capacity(buffer.len());
+        for level in (0..buffer.len()).rev() {
+            left.push(buffer[0]);
+            right.push(buffer[level]);
+            de_casteljau_step(&mut buffer[..=level], t as f64);
+        }
+        right.reverse();
+        (Self::from_homogeneous(left), Self::from_homogeneous(right))
+    }
+
+    /// Every level of the de Casteljau pyramid at `t`, starting with the control points and
+    /// ending with the single curve point. Rational curves are interpolated in homogeneous
+    /// coordinates and every level is projected back, so it ends at the same point as [`Self::evaluate`].
+    pub fn de_casteljau_levels(&self, t: f32) -> Vec<Vec<(f32, f32)>> {
+        let mut buffer = self.homogeneous();
+        let mut levels = Vec::with_capacity(buffer.len());
+        for level in (0..buffer.len()).rev() {
+            levels.push(buffer[..=level].iter().copied().map(evaluator::to_point).collect());
+            de_casteljau_step(&mut buffer[..=level], t as f64);
+        }
+        levels
+    }
+
+    /// Same curve with one degree more. The shape and parameterization stay exactly the same.
+    pub fn elevate_degree(&self) -> BezierCurve {
+        if self.is_empty() {
+            return self.clone();
+        }
+
+        let points = self.homogeneous();
+        let n = points.len();
+        let elevated = (0..=n).map(|i| {
+            let alpha = i as f64 / n as f64;
+            let previous = if i > 0 { points[i - 1] } else { points[0] };
+            let current = if i < n { points[i] } else { points[n - 1] };
+            [
+                alpha * previous[0] + (1.0 - alpha) * current[0],
+                alpha * previous[1] + (1.0 - alpha) * current[1],
+                alpha * previous[2] + (1.0 - alpha) * current[2],
+            ]
+        });
+        Self::from_homogeneous(elevated)
+    }
+
+    /// Curve with one degree less that keeps the end points and is closest to this one
+    /// in the least squares sense, i.e. it minimizes the integral of the squared distance
+    /// between the (homogeneous) curves over `[0, 1]`.
+    ///
+    /// Returns `None` for curves that are already a line or a point, and for rational curves
+    /// whose closest reduced curve would need a weight that is not positive.
+    pub fn reduce_degree(&self) -> Option<BezierCurve> {
+        let n = self.degree();
+        if n < 2 {
+            return None;
+        }
+
+        let m = n - 1;
+        let points = self.homogeneous();
+        let (start, end) = (points[0], points[n]);
+        // Integral over [0, 1] of the product of two Bernstein polynomials.
+        let product = |degree_a: usize, i: usize, degree_b: usize, j: usize| {
+            linalg::binomial(degree_a, i) * linalg::binomial(degree_b, j)
+                / ((degree_a + degree_b + 1) as f64 * linalg::binomial(degree_a + degree_b, i + j))
+        };
+
+        // Normal equations for the interior control points, the end points are fixed.
+        let interior = 1..m;
+        let matrix = interior.clone()
+            .map(|i| interior.clone().map(|j| product(m, i, m, j)).collect())
+            .collect();
+        let rhs = interior.clone().map(|i| {
+            let mut row = vec![0.0; 3];
+            for (k, point) in points.iter().enumerate() {
+                let factor = product(m, i, n, k);
+                for (value, coordinate) in row.iter_mut().zip(point) {
+                    *value += factor * coordinate;
+                }
+            }
+            for (idx, value) in row.iter_mut().enumerate() {
+                *value -= product(m, i, m, 0) * start[idx] + product(m, i, m, m) * end[idx];
+            }
+            row
+        }).collect();
+        let solution = linalg::solve(matrix, rhs)?;
+        if solution.iter().any(|row| !(row[2] > 0.0 && row[2].is_finite())) {
+            return None;
+        }
+
+        let reduced = std::iter::once(start)
+            .chain(solution.iter().map(|row| [row[0], row[1], row[2]]))
+            .chain(std::iter::once(end));
+        Some(Self::from_homogeneous(reduced))
+    }
+
+    /// Largest distance between the points of both curves at the same parameter,
+    /// sampled at `samples + 1` evenly spaced parameters.
+    pub fn max_deviation(&self, other: &BezierCurve, samples: usize) -> f32 {
+        (0..=samples.max(1))
+            .filter_map(|step| {
+                let t = step as f32 / samples.max(1) as f32;
+                let (a, b) = (self.evaluate(t)?, other.evaluate(t)?);
+                Some(((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt())
+            })
+            .fold(0.0, f32::max)
+    }
+}
+
+/// Replaces the homogeneous points by the next, one shorter level of the de Casteljau pyramid,
+/// leaving the last point as it was.
+fn de_casteljau_step(points: &mut [[f64; 3]], t: f64) {
+    for idx in 1..points.len() {
+        let (a, b) = (points[idx - 1], points[idx]);
+        points[idx - 1] = [
+            (1.0 - t) * a[0] + t * b[0],
+            (1.0 - t) * a[1] + t * b[1],
+            (1.0 - t) * a[2] + t * b[2],
+        ];
+    }
+}
+
+/// Position and first and second derivative of the homogeneous polynomial curve over `points`,
+/// read off the last three levels of the de Casteljau pyramid. The points are overwritten.
+fn derivatives_in_place(points: &mut [[f64; 3]], t: f64) -> Option<[[f64; 3]; 3]> {
+    let degree = points.len().checked_sub(1)?;
+    for level in (3..points.len()).rev() {
+        de_casteljau_step(&mut points[..=level], t);
+    }
+
+    let n = degree as f64;
+    let mut second = [0.0; 3];
+    if degree >= 2 {
+        let (a, b, c) = (points[0], points[1], points[2]);
+        let factor = n * (n - 1.0);
+        second = [factor * (a[0] - 2.0 * b[0] + c[0]), factor * (a[1] - 2.0 * b[1] + c[1]), factor * (a[2] - 2.0 * b[2] + c[2])];
+        de_casteljau_step(&mut points[..3], t);
+    }
+    let mut first = [0.0; 3];
+    if degree >= 1 {
+        let (a, b) = (points[0], points[1]);
+        first = [n * (b[0] - a[0]), n * (b[1] - a[1]), n * (b[2] - a[2])];
+        de_casteljau_step(&mut points[..2], t);
+    }
+    Some([points[0], first, second])
+}
+
+pub fn lerp(a: (f32, f32), b: (f32, f32), t: f32) -> (f32, f32) {
+    ((1.0 - t) * a.0 + t * b.0, (1.0 - t) * a.1 + t * b.1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: (f32, f32), b: (f32, f32)) {
+        assert!((a.0 - b.0).abs() < 1e-5 && (a.1 - b.1).abs() < 1e-5, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn empty_curve_has_no_points() {
+        let curve = BezierCurve::new(Vec::new());
+        assert!(curve.is_empty());
+        assert_eq!(curve.degree(), 0);
+        assert_eq!(curve.evaluate(0.5), None);
+    }
+
+    #[test]
+    fn single_point_curve_is_constant() {
+        let curve = BezierCurve::new(vec![(0.3, -0.4)]);
+        assert_eq!(curve.degree(), 0);
+        assert_eq!(curve.evaluate(0.0), Some((0.3, -0.4)));
+        assert_eq!(curve.evaluate(0.7), Some((0.3, -0.4)));
+    }
+
+    #[test]
+    fn line_is_linear_interpolation() {
+        let curve = BezierCurve::new(vec![(0.0, 0.0), (2.0, 4.0)]);
+        assert_eq!(curve.degree(), 1);
+        assert_close(curve.evaluate(0.25).unwrap(), (0.5, 1.0));
+    }
+
+    #[test]
+    fn curve_passes_through_end_points() {
+        let curve = BezierCurve::new(vec![(0.0, 0.0), (1.0, 2.0), (3.0, -1.0), (4.0, 0.5)]);
+        assert_eq!(curve.degree(), 3);
+        assert_close(curve.evaluate(0.0).unwrap(), (0.0, 0.0));
+        assert_close(curve.evaluate(1.0).unwrap(), (4.0, 0.5));
+    }
+
+    #[test]
+    fn quadratic_matches_bernstein_form() {
+        let points = [(0.0, 0.0), (1.0, 2.0), (2.0, 0.0)];
+        let curve = BezierCurve::new(points.to_vec());
+        let t = 0.3f32;
+        let b = [(1.0 - t) * (1.0 - t), 2.0 * t * (1.0 - t), t * t];
+        let expected = (
+            b[0] * points[0].0 + b[1] * points[1].0 + b[2] * points[2].0,
+            b[0] * points[0].1 + b[1] * points[1].1 + b[2] * points[2].1,
+        );
+        assert_close(curve.evaluate(t).unwrap(), expected);
+    }
+
+    #[test]
+    fn unit_weights_are_not_rational() {
+        let points = vec![(0.0, 0.0), (1.0, 2.0), (2.0, 0.0)];
+        let polynomial = BezierCurve::new(points.clone());
+        let uniform = BezierCurve::rational(points, vec![2.0, 2.0, 2.0]);
+        assert!(!polynomial.is_rational());
+        assert!(!uniform.is_rational());
+        assert_close(polynomial.evaluate(0.4).unwrap(), uniform.evaluate(0.4).unwrap());
+    }
+
+    #[test]
+    fn quarter_circle_is_exact() {
+        let center = (1.0, -2.0);
+        let radius = 3.0;
+        let curve = BezierCurve::quarter_circle(center, radius);
+        assert!(curve.is_rational());
+        for step in 0..=20 {
+            let point = curve.evaluate(step as f32 / 20.0).unwrap();
+            let distance = ((point.0 - center.0).powi(2) + (point.1 - center.1).powi(2)).sqrt();
+            assert!((distance - radius).abs() < 1e-5, "distance {} at step {}", distance, step);
+        }
+    }
+
+    #[test]
+    fn heavier_weight_pulls_curve_towards_point() {
+        let points = vec![(0.0, 0.0), (1.0, 1.0), (2.0, 0.0)];
+        let plain = BezierCurve::new(points.clone()).evaluate(0.5).unwrap();
+        let pulled = BezierCurve::rational(points, vec![1.0, 4.0, 1.0]).evaluate(0.5).unwrap();
+        assert!(pulled.1 > plain.1);
+    }
+
+    #[test]
+    fn elevation_keeps_the_shape() {
+        let curves = [
+            BezierCurve::new(vec![(0.0, 0.0), (1.0, 2.0), (3.0, -1.0), (4.0, 0.5)]),
+            BezierCurve::quarter_circle((1.0, -2.0), 3.0),
+        ];
+        for curve in curves {
+            let elevated = curve.elevate_degree();
+            assert_eq!(elevated.degree(), curve.degree() + 1);
+            assert_eq!(elevated.is_rational(), curve.is_rational());
+            for step in 0..=20 {
+                let t = step as f32 / 20.0;
+                assert_close(elevated.evaluate(t).unwrap(), curve.evaluate(t).unwrap());
+            }
+        }
+    }
+
+    #[test]
+    fn reduction_undoes_elevation() {
+        let curve = BezierCurve::new(vec![(0.0, 0.0), (1.0, 2.0), (3.0, -1.0), (4.0, 0.5)]);
+        let reduced = curve.elevate_degree().elevate_degree().reduce_degree().unwrap().reduce_degree().unwrap();
+        assert_eq!(reduced.degree(), 3);
+        for (a, b) in reduced.control_points().iter().zip(curve.control_points()) {
+            assert!((a.0 - b.0).abs() < 1e-3 && (a.1 - b.1).abs() < 1e-3, "{:?} != {:?}", a, b);
+        }
+    }
+
+    #[test]
+    fn reduction_approximates_and_keeps_end_points() {
+        let curve = BezierCurve::new(vec![(0.0, 0.0), (1.0, 3.0), (2.0, -3.0), (3.0, 3.0), (4.0, 0.0)]);
+        let reduced = curve.reduce_degree().unwrap();
+        assert_eq!(reduced.degree(), 3);
+        assert_close(reduced.evaluate(0.0).unwrap(), (0.0, 0.0));
+        assert_close(reduced.evaluate(1.0).unwrap(), (4.0, 0.0));
+        let deviation = reduced.max_deviation(&curve, 100);
+        assert!(deviation > 0.01 && deviation < 1.0, "deviation {}", deviation);
+        assert_eq!(BezierCurve::new(vec![(0.0, 0.0), (1.0, 1.0)]).reduce_degree(), None);
+    }
+
+    #[test]
+    fn reduction_rejects_non_positive_weights() {
+        let curve = BezierCurve::rational(vec![(0.0, 0.0), (1.0, 1.0), (2.0, 1.0), (3.0, 0.0)], vec![100.0, 0.01, 0.01, 100.0]);
+        assert_eq!(curve.reduce_degree(), None);
+    }
+
+    #[test]
+    fn split_parts_trace_the_curve() {
+        let curves = [
+            BezierCurve::new(vec![(0.0, 0.0), (1.0, 2.0), (3.0, -1.0), (4.0, 0.5)]),
+            BezierCurve::quarter_circle((1.0, -2.0), 3.0),
+        ];
+        let t = 0.3;
+        for curve in curves {
+            let (left, right) = curve.split(t);
+            assert_eq!(left.degree(), curve.degree());
+            assert_eq!(right.degree(), curve.degree());
+            assert_close(left.evaluate(1.0).unwrap(), curve.evaluate(t).unwrap());
+            assert_close(right.evaluate(0.0).unwrap(), curve.evaluate(t).unwrap());
+            for step in 0..=10 {
+                let s = step as f32 / 10.0;
+                assert_close(left.evaluate(s).unwrap(), curve.evaluate(s * t).unwrap());
+                assert_close(right.evaluate(s).unwrap(), curve.evaluate(t + s * (1.0 - t)).unwrap());
+            }
+        }
+    }
+
+    #[test]
+    fn derivatives_match_finite_differences() {
+        let curves = [
+            BezierCurve::new(vec![(0.0, 0.0), (1.0, 2.0), (3.0, -1.0), (4.0, 0.5)]),
+            BezierCurve::rational(vec![(0.0, 0.0), (1.0, 2.0), (3.0, -1.0), (4.0, 0.5)], vec![1.0, 2.0, 0.5, 1.5]),
+        ];
+        let (t, h) = (0.4, 1e-2);
+        for curve in curves {
+            let [point, d1, d2] = curve.derivatives(t).unwrap();
+            let (before, after) = (curve.evaluate(t - h).unwrap(), curve.evaluate(t + h).unwrap());
+            assert_close(point, curve.evaluate(t).unwrap());
+            let numeric_d1 = ((after.0 - before.0) / (2.0 * h), (after.1 - before.1) / (2.0 * h));
+            let numeric_d2 = ((after.0 - 2.0 * point.0 + before.0) / (h * h), (after.1 - 2.0 * point.1 + before.1) / (h * h));
+            assert!((d1.0 - numeric_d1.0).abs() < 1e-2 && (d1.1 - numeric_d1.1).abs() < 1e-2, "{:?} != {:?}", d1, numeric_d1);
+            assert!((d2.0 - numeric_d2.0).abs() < 0.2 && (d2.1 - numeric_d2.1).abs() < 0.2, "{:?} != {:?}", d2, numeric_d2);
+        }
+    }
+
+    #[test]
+    fn derivatives_of_long_curves_match_the_original() {
+        let curve = BezierCurve::new(vec![(0.0, 0.0), (1.0, 2.0), (3.0, -1.0), (4.0, 0.5)]);
+        let mut elevated = curve.clone();
+        while elevated.control_points().len() <= STACK_POINTS {
+            elevated = elevated.elevate_degree();
+        }
+        for step in 0..=4 {
+            let t = step as f32 / 4.0;
+            let (expected, actual) = (curve.derivatives(t).unwrap(), elevated.derivatives(t).unwrap());
+            for (a, b) in expected.iter().zip(&actual) {
+                assert!((a.0 - b.0).abs() < 1e-2 && (a.1 - b.1).abs() < 1e-2, "{:?} != {:?}", a, b);
+            }
+        }
+        assert_eq!(BezierCurve::new(vec![(1.0, 2.0)]).derivatives(0.5), Some([(1.0, 2.0), (0.0, 0.0), (0.0, 0.0)]));
+    }
+
+    #[test]
+    fn circle_has_constant_curvature() {
+        let curve = BezierCurve::quarter_circle((1.0, -2.0), 2.0);
+        for step in 0..=10 {
+            assert!((curve.curvature(step as f32 / 10.0).unwrap() - 0.5).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn projection_finds_the_closest_point() {
+        let curve = BezierCurve::quarter_circle((0.0, 0.0), 2.0);
+        let projection = curve.project((3.0, 3.0)).unwrap();
+        let expected = (std::f32::consts::SQRT_2, std::f32::consts::SQRT_2);
+        assert!((projection.point.0 - expected.0).abs() < 1e-4 && (projection.point.1 - expected.1).abs() < 1e-4);
+        assert!((projection.distance - (18.0f32.sqrt() - 2.0)).abs() < 1e-4);
+        assert!((projection.t - 0.5).abs() < 1e-4);
+
+        let beyond_end = curve.project((3.0, -1.0)).unwrap();
+        assert_eq!(beyond_end.t, 0.0);
+        assert_close(beyond_end.point, (2.0, 0.0));
+    }
+
+    #[test]
+    fn projection_picks_the_global_minimum() {
+        // A U shape with the query point between its arms, nearer to the right one.
+        let curve = BezierCurve::new(vec![(0.0, 4.0), (0.0, -4.0), (4.0, -4.0), (4.0, 4.0)]);
+        let projection = curve.project((2.6, 2.0)).unwrap();
+        assert!(projection.t > 0.5);
+        for step in 0..=100 {
+            let on_curve = curve.evaluate(step as f32 / 100.0).unwrap();
+            let distance = ((on_curve.0 - 2.6f32).powi(2) + (on_curve.1 - 2.0f32).powi(2)).sqrt();
+            assert!(projection.distance <= distance + 1e-4);
+        }
+    }
+
+    #[test]
+    fn hodograph_is_the_derivative() {
+        let curve = BezierCurve::new(vec![(0.0, 0.0), (1.0, 2.0), (3.0, -1.0), (4.0, 0.5)]);
+        let first = curve.hodograph().unwrap();
+        let second = first.hodograph().unwrap();
+        assert_eq!(first.degree(), 2);
+        assert_close(first.control_points()[0], (3.0, 6.0));
+        for step in 0..=10 {
+            let t = step as f32 / 10.0;
+            let [_, d1, d2] = curve.derivatives(t).unwrap();
+            assert_close(first.evaluate(t).unwrap(), d1);
+            assert!((second.evaluate(t).unwrap().0 - d2.0).abs() < 1e-4 && (second.evaluate(t).unwrap().1 - d2.1).abs() < 1e-4);
+        }
+        assert_eq!(BezierCurve::quarter_circle((0.0, 0.0), 1.0).hodograph(), None);
+    }
+
+    #[test]
+    fn frenet_normal_points_to_the_center_of_curvature() {
+        let center = (1.0, -2.0);
+        for radius in [2.0, -2.0] {
+            let curve = BezierCurve::quarter_circle(center, radius);
+            for step in 0..=4 {
+                let t = step as f32 / 4.0;
+                let point = curve.evaluate(t).unwrap();
+                let (tangent, normal) = curve.frenet_frame(t).unwrap();
+                assert_close(normal, ((center.0 - point.0) / radius.abs(), (center.1 - point.1) / radius.abs()));
+                assert!((tangent.0 * normal.0 + tangent.1 * normal.1).abs() < 1e-5);
+            }
+        }
+
+        let line = BezierCurve::new(vec![(0.0, 0.0), (2.0, 0.0)]);
+        assert_eq!(line.frenet_frame(0.5), Some(((1.0, 0.0), (0.0, 1.0))));
+        assert_eq!(BezierCurve::new(vec![(1.0, 1.0), (1.0, 1.0)]).frenet_frame(0.5), None);
+    }
+
+    #[test]
+    fn de_casteljau_pyramid_narrows_to_the_curve_point() {
+        let curve = BezierCurve::new(vec![(0.0, 0.0), (0.0, 2.0), (2.0, 2.0), (2.0, 0.0)]);
+        let levels = curve.de_casteljau_levels(0.5);
+        assert_eq!(levels.iter().map(|level| level.len()).collect::<Vec<_>>(), vec![4, 3, 2, 1]);
+        assert_eq!(levels[1], vec![(0.0, 1.0), (1.0, 2.0), (2.0, 1.0)]);
+        assert_close(levels[3][0], curve.evaluate(0.5).unwrap());
+
+        let arc = BezierCurve::quarter_circle((0.0, 0.0), 1.0);
+        assert_close(arc.de_casteljau_levels(0.3)[2][0], arc.evaluate(0.3).unwrap());
+        assert!(BezierCurve::new(Vec::new()).de_casteljau_levels(0.5).is_empty());
+    }
+}
@@ -0,0 +1,256 @@
+use crate::curve::bezier::{lerp, BezierCurve};
+use crate::curve::nurbs::NurbsCurve;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KnotVectorKind {
+    /// Equally spaced knots, the curve does not touch the end control points.
+    Uniform,
+    /// End knots repeated `degree + 1` times so that the curve starts and ends at the end control points.
+    Clamped,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BSplineError {
+    InvalidDegree { degree: usize, control_points: usize },
+    InvalidKnotCount { expected: usize, actual: usize },
+    DecreasingKnots,
+    EmptyDomain,
+    KnotOutsideDomain,
+    MultiplicityExceeded,
+}
+
+/// Knot vector of the given kind for `control_points` points, spanning `[0, 1]`.
+pub fn knot_vector(kind: KnotVectorKind, control_points: usize, degree: usize) -> Vec<f32> {
+    let count = control_points + degree + 1;
+    match kind {
+        KnotVectorKind::Uniform => (0..count).map(|idx| idx as f32 / (count - 1) as f32).collect(),
+        KnotVectorKind::Clamped => {
+            let spans = control_points - degree;
+            (0..count).map(|idx| {
+                let idx = idx.saturating_sub(degree).min(spans);
+                idx as f32 / spans as f32
+            }).collect()
+        },
+    }
+}
+
+/// Checks that `knots` form a valid knot vector for `control_points` points of the given degree.
+pub fn validate_knots(control_points: usize, degree: usize, knots: &[f32]) -> Result<(), BSplineError> {
+    if degree == 0 || control_points < degree + 1 {
+        return Err(BSplineError::InvalidDegree { degree, control_points });
+    }
+    if knots.len() != control_points + degree + 1 {
+        return Err(BSplineError::InvalidKnotCount { expected: control_points + degree + 1, actual: knots.len() });
+    }
+    if knots.windows(2).any(|pair| pair[1] < pair[0]) {
+        return Err(BSplineError::DecreasingKnots);
+    }
+    if knots[degree] >= knots[control_points] {
+        return Err(BSplineError::EmptyDomain);
+    }
+
+    Ok(())
+}
+
+/// Knot vector for `control_points` points made from `knots`, which are valid for the same degree but
+/// another number of points. Each missing knot is inserted in the middle of the widest span of the domain
+/// and each extra one is the domain knot closest to its neighbours, so edited knots survive adding and
+/// removing points. Returns `None` if `knots` do not fit `degree` or there are no domain knots left to remove.
+pub fn resize_knots(knots: &[f32], degree: usize, control_points: usize) -> Option<Vec<f32>> {
+    validate_knots(knots.len().checked_sub(degree + 1)?, degree, knots).ok()?;
+
+    let mut knots = knots.to_vec();
+    let count = control_points + degree + 1;
+    while knots.len() < count {
+        let end = knots.len() - degree - 1;
+        let span = (degree..end).max_by(|&a, &b| (knots[a + 1] - knots[a]).total_cmp(&(knots[b + 1] - knots[b])))?;
+        knots.insert(span + 1, 0.5 * (knots[span] + knots[span + 1]));
+    }
+    while knots.len() > count {
+        let end = knots.len() - degree - 1;
+        let idx = (degree + 1..end)
+            .filter(|idx| knots[degree] < knots[*idx] && knots[*idx] < knots[end])
+            .min_by(|&a, &b| (knots[a + 1] - knots[a - 1]).total_cmp(&(knots[b + 1] - knots[b - 1])))?;
+        knots.remove(idx);
+    }
+    validate_knots(control_points, degree, &knots).ok()?;
+    Some(knots)
+}
+
+/// Index `k` of the knot span `[knots[k], knots[k + 1])` containing `u`,
+/// the end of the domain belongs to the last non-empty span.
+pub fn knot_span(knots: &[f32], degree: usize, control_points: usize, u: f32) -> usize {
+    let last = control_points - 1;
+    if u >= knots[last + 1] {
+        let mut span = last;
+        while knots[span] == knots[span + 1] {
+            span -= 1;
+        }
+        return span;
+    }
+
+    let mut span = degree;
+    while span < last && knots[span + 1] <= u {
+        span += 1;
+    }
+    span
+}
+
+/// Number of knots equal to `u`.
+pub fn knot_multiplicity(knots: &[f32], u: f32) -> usize {
+    knots.iter().filter(|knot| **knot == u).count()
+}
+
+/// B-spline curve of arbitrary degree over a non-decreasing knot vector.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BSplineCurve {
+    control_points: Vec<(f32, f32)>,
+    degree: usize,
+    knots: Vec<f32>,
+}
+
+impl BSplineCurve {
+    /// Creates the curve, requiring `control_points.len() + degree + 1` non-decreasing knots
+    /// and at least `degree + 1` control points.
+    pub fn new(control_points: Vec<(f32, f32)>, degree: usize, knots: Vec<f32>) -> Result<Self, BSplineError> {
+        validate_knots(control_points.len(), degree, &knots)?;
+        Ok(Self { control_points, degree, knots })
+    }
+
+    pub fn control_points(&self) -> &[(f32, f32)] {
+        &self.control_points
+    }
+
+    pub fn degree(&self) -> usize {
+        self.degree
+    }
+
+    pub fn knots(&self) -> &[f32] {
+        &self.knots
+    }
+
+    /// Parameter range over which the curve is defined.
+    pub fn domain(&self) -> (f32, f32) {
+        (self.knots[self.degree], self.knots[self.control_points.len()])
+    }
+
+    /// Evaluates the curve at `u`, clamped to the domain, using de Boor's algorithm.
+    pub fn evaluate(&self, u: f32) -> (f32, f32) {
+        let (start, end) = self.domain();
+        let u = u.clamp(start, end);
+        let span = knot_span(&self.knots, self.degree, self.control_points.len(), u);
+        let p = self.degree;
+        let mut buffer: Vec<(f32, f32)> = self.control_points[span - p..=span].to_vec();
+        for r in 1..=p {
+            for j in (r..=p).rev() {
+                let i = j + span - p;
+                let denominator = self.knots[i + 1 + p - r] - self.knots[i];
+                let alpha = if denominator > 0.0 { (u - self.knots[i]) / denominator } else { 0.0 };
+                buffer[j] = lerp(buffer[j - 1], buffer[j], alpha);
+            }
+        }
+
+        buffer[p]
+    }
+
+    /// Evaluates the curve at the fraction `t` of its domain.
+    pub fn evaluate_normalized(&self, t: f32) -> (f32, f32) {
+        let (start, end) = self.domain();
+        self.evaluate(start + t * (end - start))
+    }
+
+    /// Bezier segments that together make up the curve, see [`NurbsCurve::bezier_segments`].
+    pub fn bezier_segments(&self) -> Vec<(BezierCurve, (f32, f32))> {
+        let weights = vec![1.0; self.control_points.len()];
+        NurbsCurve::new(self.control_points.clone(), weights, self.degree, self.knots.clone())
+            .map(|curve| curve.bezier_segments())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: (f32, f32), b: (f32, f32)) {
+        assert!((a.0 - b.0).abs() < 1e-4 && (a.1 - b.1).abs() < 1e-4, "{:?} != {:?}", a, b);
+    }
+
+    fn points() -> Vec<(f32, f32)> {
+        vec![(0.0, 0.0), (1.0, 2.0), (3.0, 3.0), (4.0, 0.0), (6.0, 1.0)]
+    }
+
+    #[test]
+    fn knot_vectors_have_expected_shape() {
+        assert_eq!(knot_vector(KnotVectorKind::Uniform, 3, 1), vec![0.0, 0.25, 0.5, 0.75, 1.0]);
+        assert_eq!(knot_vector(KnotVectorKind::Clamped, 4, 2), vec![0.0, 0.0, 0.0, 0.5, 1.0, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn resized_knots_keep_the_edited_ones() {
+        let knots = vec![0.0, 0.0, 0.0, 0.2, 1.0, 1.0, 1.0];
+        assert_eq!(resize_knots(&knots, 2, 5), Some(vec![0.0, 0.0, 0.0, 0.2, 0.6, 1.0, 1.0, 1.0]));
+        assert_eq!(resize_knots(&knots, 2, 3), Some(vec![0.0, 0.0, 0.0, 1.0, 1.0, 1.0]));
+        assert_eq!(resize_knots(&knots, 2, 2), None);
+        assert_eq!(resize_knots(&knots, 3, 5), None);
+    }
+
+    #[test]
+    fn invalid_inputs_are_rejected() {
+        assert_eq!(
+            BSplineCurve::new(points(), 5, vec![0.0; 11]),
+            Err(BSplineError::InvalidDegree { degree: 5, control_points: 5 })
+        );
+        assert_eq!(
+            BSplineCurve::new(points(), 2, vec![0.0; 7]),
+            Err(BSplineError::InvalidKnotCount { expected: 8, actual: 7 })
+        );
+        assert_eq!(
+            BSplineCurve::new(points(), 1, vec![0.0, 0.2, 0.1, 0.4, 0.5, 0.6, 0.7]),
+            Err(BSplineError::DecreasingKnots)
+        );
+        assert_eq!(
+            BSplineCurve::new(points(), 2, vec![0.0, 0.0, 0.5, 0.5, 0.5, 0.5, 1.0, 1.0]),
+            Err(BSplineError::EmptyDomain)
+        );
+    }
+
+    #[test]
+    fn clamped_curve_of_full_degree_is_a_bezier_curve() {
+        let degree = points().len() - 1;
+        let knots = knot_vector(KnotVectorKind::Clamped, points().len(), degree);
+        let bspline = BSplineCurve::new(points(), degree, knots).unwrap();
+        let bezier = BezierCurve::new(points());
+        for step in 0..=10 {
+            let t = step as f32 / 10.0;
+            assert_close(bspline.evaluate_normalized(t), bezier.evaluate(t).unwrap());
+        }
+    }
+
+    #[test]
+    fn clamped_curve_interpolates_end_points() {
+        let knots = knot_vector(KnotVectorKind::Clamped, points().len(), 3);
+        let curve = BSplineCurve::new(points(), 3, knots).unwrap();
+        assert_close(curve.evaluate_normalized(0.0), (0.0, 0.0));
+        assert_close(curve.evaluate_normalized(1.0), (6.0, 1.0));
+    }
+
+    #[test]
+    fn uniform_linear_spline_passes_through_control_points() {
+        let knots = knot_vector(KnotVectorKind::Uniform, points().len(), 1);
+        let curve = BSplineCurve::new(points(), 1, knots.clone()).unwrap();
+        for (idx, point) in points().iter().enumerate() {
+            assert_close(curve.evaluate(knots[idx + 1]), *point);
+        }
+    }
+
+    #[test]
+    fn uniform_cubic_matches_blending_functions() {
+        let knots = knot_vector(KnotVectorKind::Uniform, points().len(), 3);
+        let curve = BSplineCurve::new(points(), 3, knots.clone()).unwrap();
+        // At the start of a span a uniform cubic is (P0 + 4 P1 + P2) / 6.
+        let p = points();
+        let expected = ((p[0].0 + 4.0 * p[1].0 + p[2].0) / 6.0, (p[0].1 + 4.0 * p[1].1 + p[2].1) / 6.0);
+        assert_close(curve.evaluate(knots[3]), expected);
+    }
+}
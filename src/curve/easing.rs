@@ -0,0 +1,231 @@
+use crate::curve::bezier::BezierCurve;
+
+/// Mapping of the linear progress of an animation in `[0, 1]` to the animated value, which
+/// starts at 0 and ends at 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Easing {
+    Linear,
+    /// Starts slowly, cubic.
+    EaseIn,
+    /// Ends slowly, cubic.
+    EaseOut,
+    /// Starts and ends slowly, the two cubic halves meet in the middle.
+    EaseInOut,
+}
+
+impl Easing {
+    pub fn apply(&self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t.powi(3),
+            Easing::EaseOut => 1.0 - (1.0 - t).powi(3),
+            Easing::EaseInOut if t < 0.5 => 4.0 * t.powi(3),
+            Easing::EaseInOut => 1.0 - 4.0 * (1.0 - t).powi(3),
+        }
+    }
+
+    /// Inverse of [`Self::apply`], the progress at which the eased value reaches `value`.
+    pub fn invert(&self, value: f32) -> f32 {
+        let value = value.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => value,
+            Easing::EaseIn => value.cbrt(),
+            Easing::EaseOut => 1.0 - (1.0 - value).cbrt(),
+            Easing::EaseInOut if value < 0.5 => (value / 4.0).cbrt(),
+            Easing::EaseInOut => 1.0 - ((1.0 - value) / 4.0).cbrt(),
+        }
+    }
+}
+
+/// Steps of bisection after Newton's method failed to converge, enough for f32 precision.
+const BISECTION_STEPS: usize = 32;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EasingParseError {
+    /// The text is neither `cubic-bezier(...)` nor one of the named CSS easings.
+    UnknownFunction,
+    WrongArgumentCount(usize),
+    InvalidNumber(String),
+    /// An x coordinate lies outside `[0, 1]`, which CSS rejects.
+    XOutOfRange,
+}
+
+/// CSS timing function, a cubic Bezier curve from `(0, 0)` to `(1, 1)` whose two inner control points
+/// have x coordinates in `[0, 1]`. That keeps x monotonic in the curve parameter, so y is a function of x.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CubicBezierEasing {
+    first: (f32, f32),
+    second: (f32, f32),
+}
+
+impl CubicBezierEasing {
+    /// Easing with the given inner control points, whose x coordinates are clamped to `[0, 1]`.
+    pub fn new(first: (f32, f32), second: (f32, f32)) -> Self {
+        Self {
+            first: (first.0.clamp(0.0, 1.0), first.1),
+            second: (second.0.clamp(0.0, 1.0), second.1),
+        }
+    }
+
+    /// The CSS `ease` timing function.
+    pub fn ease() -> Self {
+        Self::new((0.25, 0.1), (0.25, 1.0))
+    }
+
+    pub fn handles(&self) -> [(f32, f32); 2] {
+        [self.first, self.second]
+    }
+
+    pub fn curve(&self) -> BezierCurve {
+        BezierCurve::new(vec![(0.0, 0.0), self.first, self.second, (1.0, 1.0)])
+    }
+
+    fn coordinate(a: f32, b: f32, t: f32) -> f32 {
+        let s = 1.0 - t;
+        3.0 * s * s * t * a + 3.0 * s * t * t * b + t * t * t
+    }
+
+    /// Value of the easing at `x`, the y coordinate of the curve point with that x coordinate.
+    ///
+    /// The parameter of that point is found with Newton's method on `x(t) - x`, and by bisection
+    /// where the slope is too flat for Newton, which always works because `x(t)` never decreases.
+    pub fn y_for_x(&self, x: f32) -> f32 {
+        let x = x.clamp(0.0, 1.0);
+        let (x1, x2) = (self.first.0, self.second.0);
+        let x_at = |t: f32| Self::coordinate(x1, x2, t) - x;
+        let slope = |t: f32| {
+            let s = 1.0 - t;
+            3.0 * s * s * x1 + 6.0 * s * t * (x2 - x1) + 3.0 * t * t * (1.0 - x2)
+        };
+
+        let mut t = x;
+        for _ in 0..8 {
+            let (error, derivative) = (x_at(t), slope(t));
+            if error.abs() < 1e-6 {
+                return Self::coordinate(self.first.1, self.second.1, t);
+            }
+            if derivative.abs() < 1e-6 {
+                break;
+            }
+            t = (t - error / derivative).clamp(0.0, 1.0);
+        }
+
+        let (mut low, mut high) = (0.0, 1.0);
+        for _ in 0..BISECTION_STEPS {
+            let middle = 0.5 * (low + high);
+            if x_at(middle) < 0.0 {
+                low = middle;
+            } else {
+                high = middle;
+            }
+        }
+        let t = 0.5 * (low + high);
+        Self::coordinate(self.first.1, self.second.1, t)
+    }
+
+    /// The easing as CSS, e.g. `cubic-bezier(0.25, 0.1, 0.25, 1)`.
+    pub fn to_css(&self) -> String {
+        let number = |value: f32| {
+            let text = format!("{:.3}", value);
+            text.trim_end_matches('0').trim_end_matches('.').to_string()
+        };
+        format!(
+            "cubic-bezier({}, {}, {}, {})",
+            number(self.first.0), number(self.first.1), number(self.second.0), number(self.second.1),
+        )
+    }
+
+    /// Reads a CSS `cubic-bezier(x1, y1, x2, y2)` or one of the named easings `linear`, `ease`,
+    /// `ease-in`, `ease-out` and `ease-in-out`. A trailing semicolon is ignored.
+    pub fn parse_css(text: &str) -> Result<Self, EasingParseError> {
+        let text = text.trim().trim_end_matches(';').trim();
+        match text {
+            "linear" => return Ok(Self::new((0.0, 0.0), (1.0, 1.0))),
+            "ease" => return Ok(Self::ease()),
+            "ease-in" => return Ok(Self::new((0.42, 0.0), (1.0, 1.0))),
+            "ease-out" => return Ok(Self::new((0.0, 0.0), (0.58, 1.0))),
+            "ease-in-out" => return Ok(Self::new((0.42, 0.0), (0.58, 1.0))),
+            _ => (),
+        }
+
+        let arguments = text.strip_prefix("cubic-bezier")
+            .map(str::trim_start)
+            .and_then(|rest| rest.strip_prefix('('))
+            .and_then(|rest| rest.strip_suffix(')'))
+            .ok_or(EasingParseError::UnknownFunction)?;
+        let values = arguments.split(',')
+            .map(|argument| argument.trim().parse::<f32>().map_err(|_| EasingParseError::InvalidNumber(argument.trim().to_string())))
+            .collect::<Result<Vec<f32>, _>>()?;
+        if values.len() != 4 {
+            return Err(EasingParseError::WrongArgumentCount(values.len()));
+        }
+        if !(0.0..=1.0).contains(&values[0]) || !(0.0..=1.0).contains(&values[2]) {
+            return Err(EasingParseError::XOutOfRange);
+        }
+        Ok(Self::new((values[0], values[1]), (values[2], values[3])))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn easings_start_at_zero_and_end_at_one() {
+        for easing in [Easing::Linear, Easing::EaseIn, Easing::EaseOut, Easing::EaseInOut] {
+            assert_eq!(easing.apply(0.0), 0.0);
+            assert_eq!(easing.apply(1.0), 1.0);
+            assert_eq!(easing.apply(2.0), 1.0);
+            let samples: Vec<f32> = (0..=100).map(|step| easing.apply(step as f32 / 100.0)).collect();
+            assert!(samples.windows(2).all(|pair| pair[0] <= pair[1]), "{:?} is not monotonic", easing);
+        }
+        assert!(Easing::EaseIn.apply(0.5) < 0.5 && Easing::EaseOut.apply(0.5) > 0.5);
+        assert_eq!(Easing::EaseInOut.apply(0.5), 0.5);
+    }
+
+    #[test]
+    fn invert_undoes_apply() {
+        for easing in [Easing::Linear, Easing::EaseIn, Easing::EaseOut, Easing::EaseInOut] {
+            for step in 0..=20 {
+                let t = step as f32 / 20.0;
+                assert!((easing.invert(easing.apply(t)) - t).abs() < 1e-3, "{:?} at {}", easing, t);
+            }
+        }
+    }
+
+    #[test]
+    fn y_for_x_follows_the_curve() {
+        let easing = CubicBezierEasing::ease();
+        let curve = easing.curve();
+        for step in 0..=20 {
+            let point = curve.evaluate(step as f32 / 20.0).unwrap();
+            assert!((easing.y_for_x(point.0) - point.1).abs() < 1e-4);
+        }
+        assert_eq!(easing.y_for_x(0.0), 0.0);
+        assert!((easing.y_for_x(1.0) - 1.0).abs() < 1e-6);
+
+        // Vertical start, where Newton's method has no slope to work with.
+        let steep = CubicBezierEasing::new((0.0, 1.0), (0.0, 1.0));
+        let point = steep.curve().evaluate(0.3).unwrap();
+        assert!((steep.y_for_x(point.0) - point.1).abs() < 1e-3);
+    }
+
+    #[test]
+    fn css_round_trip() {
+        let easing = CubicBezierEasing::new((0.68, -0.55), (0.265, 1.55));
+        assert_eq!(easing.to_css(), "cubic-bezier(0.68, -0.55, 0.265, 1.55)");
+        assert_eq!(CubicBezierEasing::parse_css(&easing.to_css()), Ok(easing));
+        assert_eq!(CubicBezierEasing::parse_css(" cubic-bezier( 0.25,0.1 , 0.25, 1 );"), Ok(CubicBezierEasing::ease()));
+        assert_eq!(CubicBezierEasing::parse_css("ease"), Ok(CubicBezierEasing::ease()));
+        assert_eq!(CubicBezierEasing::new((-1.0, 0.0), (2.0, 1.0)).handles(), [(0.0, 0.0), (1.0, 1.0)]);
+    }
+
+    #[test]
+    fn invalid_css_is_rejected() {
+        assert_eq!(CubicBezierEasing::parse_css("steps(4)"), Err(EasingParseError::UnknownFunction));
+        assert_eq!(CubicBezierEasing::parse_css("cubic-bezier(0, 1, 1)"), Err(EasingParseError::WrongArgumentCount(3)));
+        assert_eq!(CubicBezierEasing::parse_css("cubic-bezier(0, a, 1, 1)"), Err(EasingParseError::InvalidNumber("a".to_string())));
+        assert_eq!(CubicBezierEasing::parse_css("cubic-bezier(1.5, 0, 1, 1)"), Err(EasingParseError::XOutOfRange));
+    }
+}
@@ -0,0 +1,180 @@
+use crate::curve::bezier::BezierCurve;
+
+/// Curves with up to this many control points are evaluated in a buffer on the stack.
+pub(crate) const STACK_POINTS: usize = 32;
+
+/// Runs the de Casteljau algorithm on homogeneous points, overwriting them, and returns the curve point.
+pub(crate) fn de_casteljau_in_place(buffer: &mut [[f64; 3]], t: f64) -> Option<[f64; 3]> {
+    for level in (1..buffer.len()).rev() {
+        for idx in 0..level {
+            let (a, b) = (buffer[idx], buffer[idx + 1]);
+            buffer[idx] = [
+                (1.0 - t) * a[0] + t * b[0],
+                (1.0 - t) * a[1] + t * b[1],
+                (1.0 - t) * a[2] + t * b[2],
+            ];
+        }
+    }
+    buffer.first().copied()
+}
+
+pub(crate) fn to_point(homogeneous: [f64; 3]) -> (f32, f32) {
+    ((homogeneous[0] / homogeneous[2]) as f32, (homogeneous[1] / homogeneous[2]) as f32)
+}
+
+/// Evaluates one curve many times without allocating. The control points are converted to
+/// homogeneous f64 coordinates once and every evaluation works in a reused scratch buffer,
+/// which keeps high degrees accurate and fast.
+#[derive(Debug, Clone, Default)]
+pub struct BezierEvaluator {
+    homogeneous: Vec<[f64; 3]>,
+    scratch: Vec<[f64; 3]>,
+}
+
+impl BezierEvaluator {
+    pub fn new(curve: &BezierCurve) -> Self {
+        let mut evaluator = Self::default();
+        evaluator.set_curve(curve);
+        evaluator
+    }
+
+    /// Switches to another curve, reusing the buffers.
+    pub fn set_curve(&mut self, curve: &BezierCurve) {
+        self.homogeneous.clear();
+        self.homogeneous.extend(curve.control_points().iter().zip(curve.weights()).map(|(point, weight)| {
+            let weight = *weight as f64;
+            [point.0 as f64 * weight, point.1 as f64 * weight, weight]
+        }));
+    }
+
+    /// Same as [`BezierCurve::evaluate`].
+    pub fn evaluate(&mut self, t: f32) -> Option<(f32, f32)> {
+        self.scratch.clear();
+        self.scratch.extend_from_slice(&self.homogeneous);
+        de_casteljau_in_place(&mut self.scratch, t as f64).map(to_point)
+    }
+
+    /// Replaces the contents of `out` by the curve points at `samples + 1` evenly spaced parameters.
+    pub fn sample(&mut self, samples: usize, out: &mut Vec<(f32, f32)>) {
+        out.clear();
+        let samples = samples.max(1);
+        for step in 0..=samples {
+            out.extend(self.evaluate(step as f32 / samples as f32));
+        }
+    }
+}
+
+/// Bernstein polynomials of one degree at evenly spaced parameters, computed once in f64.
+///
+/// Sampling a curve with the table is a weighted sum per sample instead of the quadratic de Casteljau
+/// work, which pays off when many curves of the same degree, or one curve over many frames, are sampled
+/// at the same parameters.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BernsteinTable {
+    degree: usize,
+    samples: usize,
+    values: Vec<f64>,
+}
+
+impl BernsteinTable {
+    /// Table for curves of `degree` at the `samples + 1` parameters `i / samples`.
+    pub fn uniform(degree: usize, samples: usize) -> Self {
+        let samples = samples.max(1);
+        let mut values = Vec::with_capacity((samples + 1) * (degree + 1));
+        let mut row = vec![0.0f64; degree + 1];
+        for step in 0..=samples {
+            let t = step as f64 / samples as f64;
+            // Raises the degree one at a time, B(k, j) = (1 - t) B(k - 1, j) + t B(k - 1, j - 1).
+            row.fill(0.0);
+            row[0] = 1.0;
+            for k in 1..=degree {
+                for j in (1..=k).rev() {
+                    row[j] = (1.0 - t) * row[j] + t * row[j - 1];
+                }
+                row[0] *= 1.0 - t;
+            }
+            values.extend_from_slice(&row);
+        }
+        Self { degree, samples, values }
+    }
+
+    pub fn degree(&self) -> usize {
+        self.degree
+    }
+
+    pub fn samples(&self) -> usize {
+        self.samples
+    }
+
+    /// Replaces the contents of `out` by the points of `curve` at the parameters of the table.
+    /// The curve must have the degree of the table.
+    pub fn sample(&self, curve: &BezierCurve, out: &mut Vec<(f32, f32)>) {
+        assert_eq!(curve.degree(), self.degree, "the curve needs the degree of the table");
+        out.clear();
+        if curve.is_empty() {
+            return;
+        }
+
+        for basis in self.values.chunks_exact(self.degree + 1) {
+            let mut sum = [0.0f64; 3];
+            for ((point, weight), value) in curve.control_points().iter().zip(curve.weights()).zip(basis) {
+                let factor = value * *weight as f64;
+                sum[0] += factor * point.0 as f64;
+                sum[1] += factor * point.1 as f64;
+                sum[2] += factor;
+            }
+            out.push(to_point(sum));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: (f32, f32), b: (f32, f32)) {
+        assert!((a.0 - b.0).abs() < 1e-4 && (a.1 - b.1).abs() < 1e-4, "{:?} != {:?}", a, b);
+    }
+
+    fn wiggly_curve(count: usize) -> BezierCurve {
+        let control_points = (0..count).map(|idx| (idx as f32, if idx % 2 == 0 { 1.0 } else { -1.0 })).collect();
+        let weights = (0..count).map(|idx| 1.0 + (idx % 3) as f32 * 0.5).collect();
+        BezierCurve::rational(control_points, weights)
+    }
+
+    #[test]
+    fn evaluator_agrees_with_the_curve() {
+        for count in [1, 4, STACK_POINTS, STACK_POINTS + 9] {
+            let curve = wiggly_curve(count);
+            let mut evaluator = BezierEvaluator::new(&curve);
+            let mut samples = Vec::new();
+            evaluator.sample(10, &mut samples);
+            assert_eq!(samples.len(), 11);
+            for (step, sample) in samples.iter().enumerate() {
+                assert_close(*sample, curve.evaluate(step as f32 / 10.0).unwrap());
+            }
+        }
+        assert_eq!(BezierEvaluator::new(&BezierCurve::new(Vec::new())).evaluate(0.5), None);
+    }
+
+    #[test]
+    fn bernstein_table_agrees_with_de_casteljau() {
+        let curve = wiggly_curve(41);
+        let table = BernsteinTable::uniform(curve.degree(), 50);
+        let (mut from_table, mut from_evaluator) = (Vec::new(), Vec::new());
+        table.sample(&curve, &mut from_table);
+        BezierEvaluator::new(&curve).sample(50, &mut from_evaluator);
+        assert_eq!(from_table.len(), 51);
+        for (a, b) in from_table.iter().zip(&from_evaluator) {
+            assert_close(*a, *b);
+        }
+    }
+
+    #[test]
+    fn bernstein_values_sum_to_one() {
+        let table = BernsteinTable::uniform(30, 7);
+        for row in table.values.chunks_exact(31) {
+            assert!((row.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+        }
+    }
+}
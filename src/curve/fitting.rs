@@ -0,0 +1,318 @@
+use crate::curve::bezier::BezierCurve;
+
+/// Limit on the Newton reparameterization rounds before a range is split. The rounds also stop
+/// as soon as one does not reduce the error.
+const MAX_REPARAMETERIZATIONS: usize = 4;
+/// Factor of the tolerance below which a fit is close enough to be improved by reparameterization.
+const REPARAMETERIZATION_FACTOR: f32 = 20.0;
+
+fn sub(a: (f32, f32), b: (f32, f32)) -> (f32, f32) {
+    (a.0 - b.0, a.1 - b.1)
+}
+
+fn dot(a: (f32, f32), b: (f32, f32)) -> f32 {
+    a.0 * b.0 + a.1 * b.1
+}
+
+fn scale(a: (f32, f32), factor: f32) -> (f32, f32) {
+    (a.0 * factor, a.1 * factor)
+}
+
+fn normalize(a: (f32, f32)) -> (f32, f32) {
+    let length = dot(a, a).sqrt();
+    if length > 0.0 { scale(a, 1.0 / length) } else { a }
+}
+
+/// Approximates a polyline, e.g. sampled mouse positions, with consecutive cubic segments that stay within
+/// `tolerance` of every point. Consecutive duplicate points are ignored.
+///
+/// Follows Schneider's algorithm from Graphics Gems: a cubic with the end points of a range and
+/// the estimated end tangent directions is fitted by least squares over the chord length parameters of
+/// the points. A fit that is nearly good enough is improved by moving the parameters to the closest
+/// curve points with Newton steps, otherwise the range is split at the worst point. The segments meet with
+/// matching tangent directions.
+pub fn fit_cubics(points: &[(f32, f32)], tolerance: f32) -> Vec<BezierCurve> {
+    let mut points = points.to_vec();
+    points.dedup();
+    let mut segments = Vec::new();
+    if points.len() < 2 {
+        return segments;
+    }
+
+    let last = points.len() - 1;
+    let start_tangent = normalize(sub(points[1], points[0]));
+    let end_tangent = normalize(sub(points[last - 1], points[last]));
+    fit_range(&points, start_tangent, end_tangent, tolerance.max(f32::EPSILON), &mut segments);
+    segments
+}
+
+/// Fits the points with cubic segments leaving the first point in the direction `start_tangent` and
+/// arriving at the last point from the direction `end_tangent`, which points back into the range.
+fn fit_range(points: &[(f32, f32)], start_tangent: (f32, f32), end_tangent: (f32, f32), tolerance: f32, segments: &mut Vec<BezierCurve>) {
+    let (first, last) = (points[0], points[points.len() - 1]);
+    if points.len() == 2 {
+        let handle = dot(sub(last, first), sub(last, first)).sqrt() / 3.0;
+        segments.push(cubic(first, last, start_tangent, end_tangent, (handle, handle)));
+        return;
+    }
+
+    let mut parameters = chord_length_parameters(points);
+    let mut curve = least_squares_cubic(points, &parameters, start_tangent, end_tangent);
+    let (mut error, mut split) = max_error(points, &curve, &parameters);
+    if error <= tolerance {
+        segments.push(curve);
+        return;
+    }
+
+    if error <= REPARAMETERIZATION_FACTOR * tolerance {
+        for _ in 0..MAX_REPARAMETERIZATIONS {
+            parameters = reparameterize(points, &curve, &parameters);
+            let refitted = least_squares_cubic(points, &parameters, start_tangent, end_tangent);
+            let (refitted_error, refitted_split) = max_error(points, &refitted, &parameters);
+            if refitted_error >= error {
+                break;
+            }
+            (curve, error, split) = (refitted, refitted_error, refitted_split);
+            if error <= tolerance {
+                segments.push(curve);
+                return;
+            }
+        }
+    }
+
+    let center_tangent = normalize(sub(points[split - 1], points[split + 1]));
+    fit_range(&points[..=split], start_tangent, center_tangent, tolerance, segments);
+    fit_range(&points[split..], scale(center_tangent, -1.0), end_tangent, tolerance, segments);
+}
+
+/// Approximates a polyline with consecutive quadratic segments that stay within `tolerance` of every
+/// point, like [`fit_cubics`]. A quadratic has a single handle, so it is fitted freely by least squares
+/// and the segments share their end points but not necessarily their tangent directions.
+pub fn fit_quadratics(points: &[(f32, f32)], tolerance: f32) -> Vec<BezierCurve> {
+    let mut points = points.to_vec();
+    points.dedup();
+    let mut segments = Vec::new();
+    if points.len() >= 2 {
+        fit_quadratic_range(&points, tolerance.max(f32::EPSILON), &mut segments);
+    }
+    segments
+}
+
+fn fit_quadratic_range(points: &[(f32, f32)], tolerance: f32, segments: &mut Vec<BezierCurve>) {
+    let (first, last) = (points[0], points[points.len() - 1]);
+    if points.len() == 2 {
+        segments.push(BezierCurve::new(vec![first, scale((first.0 + last.0, first.1 + last.1), 0.5), last]));
+        return;
+    }
+
+    let mut parameters = chord_length_parameters(points);
+    let mut curve = least_squares_quadratic(points, &parameters);
+    let (mut error, mut split) = max_error(points, &curve, &parameters);
+    if error > tolerance && error <= REPARAMETERIZATION_FACTOR * tolerance {
+        for _ in 0..MAX_REPARAMETERIZATIONS {
+            parameters = reparameterize(points, &curve, &parameters);
+            let refitted = least_squares_quadratic(points, &parameters);
+            let (refitted_error, refitted_split) = max_error(points, &refitted, &parameters);
+            if refitted_error >= error {
+                break;
+            }
+            (curve, error, split) = (refitted, refitted_error, refitted_split);
+            if error <= tolerance {
+                break;
+            }
+        }
+    }
+    if error <= tolerance {
+        segments.push(curve);
+        return;
+    }
+
+    fit_quadratic_range(&points[..=split], tolerance, segments);
+    fit_quadratic_range(&points[split..], tolerance, segments);
+}
+
+/// Quadratic through the end points whose handle minimizes the squared distances between the points
+/// and the curve at their parameters.
+fn least_squares_quadratic(points: &[(f32, f32)], parameters: &[f32]) -> BezierCurve {
+    let (first, last) = (points[0], points[points.len() - 1]);
+    let (mut weight_sum, mut handle) = (0.0, (0.0, 0.0));
+    for (point, &u) in points.iter().zip(parameters) {
+        let basis = [(1.0 - u) * (1.0 - u), 2.0 * u * (1.0 - u), u * u];
+        let remainder = sub(*point, (first.0 * basis[0] + last.0 * basis[2], first.1 * basis[0] + last.1 * basis[2]));
+        weight_sum += basis[1] * basis[1];
+        handle = (handle.0 + basis[1] * remainder.0, handle.1 + basis[1] * remainder.1);
+    }
+    let handle = if weight_sum > f32::EPSILON {
+        scale(handle, 1.0 / weight_sum)
+    } else {
+        scale((first.0 + last.0, first.1 + last.1), 0.5)
+    };
+    BezierCurve::new(vec![first, handle, last])
+}
+
+fn cubic(first: (f32, f32), last: (f32, f32), start_tangent: (f32, f32), end_tangent: (f32, f32), handles: (f32, f32)) -> BezierCurve {
+    BezierCurve::new(vec![
+        first,
+        (first.0 + start_tangent.0 * handles.0, first.1 + start_tangent.1 * handles.0),
+        (last.0 + end_tangent.0 * handles.1, last.1 + end_tangent.1 * handles.1),
+        last,
+    ])
+}
+
+/// Parameters of the points proportional to the length of the polyline up to them.
+fn chord_length_parameters(points: &[(f32, f32)]) -> Vec<f32> {
+    let mut parameters = Vec::with_capacity(points.len());
+    let mut length = 0.0;
+    parameters.push(0.0);
+    for pair in points.windows(2) {
+        length += dot(sub(pair[1], pair[0]), sub(pair[1], pair[0])).sqrt();
+        parameters.push(length);
+    }
+    parameters.iter().map(|parameter| parameter / length).collect()
+}
+
+/// Cubic through the end points whose handle lengths along the given tangents minimize the squared
+/// distances between the points and the curve at their parameters.
+fn least_squares_cubic(points: &[(f32, f32)], parameters: &[f32], start_tangent: (f32, f32), end_tangent: (f32, f32)) -> BezierCurve {
+    let (first, last) = (points[0], points[points.len() - 1]);
+    let mut matrix = [[0.0f32; 2]; 2];
+    let mut rhs = [0.0f32; 2];
+    for (point, &u) in points.iter().zip(parameters) {
+        let basis = [(1.0 - u).powi(3), 3.0 * u * (1.0 - u).powi(2), 3.0 * u * u * (1.0 - u), u.powi(3)];
+        let a = [scale(start_tangent, basis[1]), scale(end_tangent, basis[2])];
+        matrix[0][0] += dot(a[0], a[0]);
+        matrix[0][1] += dot(a[0], a[1]);
+        matrix[1][1] += dot(a[1], a[1]);
+        let endpoints_only = (
+            first.0 * (basis[0] + basis[1]) + last.0 * (basis[2] + basis[3]),
+            first.1 * (basis[0] + basis[1]) + last.1 * (basis[2] + basis[3]),
+        );
+        let remainder = sub(*point, endpoints_only);
+        rhs[0] += dot(a[0], remainder);
+        rhs[1] += dot(a[1], remainder);
+    }
+    matrix[1][0] = matrix[0][1];
+
+    let determinant = matrix[0][0] * matrix[1][1] - matrix[0][1] * matrix[1][0];
+    let chord = dot(sub(last, first), sub(last, first)).sqrt();
+    let handles = if determinant.abs() > f32::EPSILON * matrix[0][0] * matrix[1][1] {
+        (
+            (rhs[0] * matrix[1][1] - rhs[1] * matrix[0][1]) / determinant,
+            (matrix[0][0] * rhs[1] - matrix[1][0] * rhs[0]) / determinant,
+        )
+    } else {
+        (0.0, 0.0)
+    };
+
+    // Handles that are degenerate or point backwards make for loops, a third of the chord is a safe guess.
+    let minimum = 1e-6 * chord;
+    if handles.0 < minimum || handles.1 < minimum {
+        return cubic(first, last, start_tangent, end_tangent, (chord / 3.0, chord / 3.0));
+    }
+    cubic(first, last, start_tangent, end_tangent, handles)
+}
+
+/// Largest distance between a point and the curve at its parameter, and the index of that point
+/// which is never the first or last one.
+fn max_error(points: &[(f32, f32)], curve: &BezierCurve, parameters: &[f32]) -> (f32, usize) {
+    let mut worst = (0.0, points.len() / 2);
+    for idx in 1..points.len() - 1 {
+        let offset = sub(curve.evaluate(parameters[idx]).unwrap_or(points[idx]), points[idx]);
+        let distance = dot(offset, offset).sqrt();
+        if distance > worst.0 {
+            worst = (distance, idx);
+        }
+    }
+    worst
+}
+
+/// Moves every parameter by one Newton step towards the curve point closest to its point.
+fn reparameterize(points: &[(f32, f32)], curve: &BezierCurve, parameters: &[f32]) -> Vec<f32> {
+    points.iter().zip(parameters).map(|(point, &u)| {
+        let [position, d1, d2] = match curve.derivatives(u) {
+            Some(derivatives) => derivatives,
+            None => return u,
+        };
+        let offset = sub(position, *point);
+        let denominator = dot(d1, d1) + dot(offset, d2);
+        if denominator.abs() <= f32::EPSILON {
+            return u;
+        }
+        (u - dot(offset, d1) / denominator).clamp(0.0, 1.0)
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn distance_to_segments(point: (f32, f32), segments: &[BezierCurve]) -> f32 {
+        segments.iter()
+            .flat_map(|segment| segment.project(point))
+            .map(|projection| projection.distance)
+            .fold(f32::MAX, f32::min)
+    }
+
+    #[test]
+    fn samples_of_a_cubic_fit_one_segment() {
+        let curve = BezierCurve::new(vec![(0.0, 0.0), (100.0, 200.0), (300.0, 200.0), (400.0, 0.0)]);
+        let points: Vec<(f32, f32)> = (0..=200).map(|step| curve.evaluate(step as f32 / 200.0).unwrap()).collect();
+        let segments = fit_cubics(&points, 1.0);
+        assert_eq!(segments.len(), 1);
+        assert_eq!(segments[0].control_points()[0], (0.0, 0.0));
+        assert_eq!(segments[0].control_points()[3], (400.0, 0.0));
+    }
+
+    #[test]
+    fn zigzag_is_split_into_smooth_segments_within_tolerance() {
+        let points: Vec<(f32, f32)> = (0..=200)
+            .map(|step| {
+                let x = step as f32;
+                (x, 40.0 * (x / 15.0).sin())
+            })
+            .collect();
+        let tolerance = 1.0;
+        let segments = fit_cubics(&points, tolerance);
+        assert!(segments.len() > 1);
+        for point in &points {
+            assert!(distance_to_segments(*point, &segments) <= tolerance * 1.01);
+        }
+        for pair in segments.windows(2) {
+            let (before, after) = (pair[0].control_points(), pair[1].control_points());
+            assert_eq!(before[3], after[0]);
+            let (incoming, outgoing) = (normalize(sub(before[3], before[2])), normalize(sub(after[1], after[0])));
+            assert!(dot(incoming, outgoing) > 0.999);
+        }
+    }
+
+    #[test]
+    fn quadratics_stay_within_tolerance() {
+        let points: Vec<(f32, f32)> = (0..=200)
+            .map(|step| {
+                let x = step as f32;
+                (x, 40.0 * (x / 15.0).sin())
+            })
+            .collect();
+        let tolerance = 1.0;
+        let segments = fit_quadratics(&points, tolerance);
+        assert!(segments.len() > 1);
+        assert!(segments.iter().all(|segment| segment.degree() == 2));
+        for point in &points {
+            assert!(distance_to_segments(*point, &segments) <= tolerance * 1.01);
+        }
+        for pair in segments.windows(2) {
+            assert_eq!(pair[0].control_points()[2], pair[1].control_points()[0]);
+        }
+
+        let parabola = BezierCurve::new(vec![(0.0, 0.0), (50.0, 100.0), (100.0, 0.0)]);
+        let samples: Vec<(f32, f32)> = (0..=100).map(|step| parabola.evaluate(step as f32 / 100.0).unwrap()).collect();
+        assert_eq!(fit_quadratics(&samples, 0.5).len(), 1);
+    }
+
+    #[test]
+    fn too_few_points() {
+        assert!(fit_cubics(&[(1.0, 1.0), (1.0, 1.0)], 1.0).is_empty());
+        let segments = fit_cubics(&[(0.0, 0.0), (3.0, 0.0)], 1.0);
+        assert_eq!(segments[0].control_points(), &[(0.0, 0.0), (1.0, 0.0), (2.0, 0.0), (3.0, 0.0)]);
+    }
+}
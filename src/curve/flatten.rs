@@ -0,0 +1,101 @@
+/// Number of equal parameter intervals flattened independently, so that features
+/// smaller than a whole interval cannot hide between the probed parameters.
+const INITIAL_SEGMENTS: usize = 8;
+/// Limit on the recursion depth, which caps the vertex count for degenerate input.
+const MAX_DEPTH: u32 = 16;
+
+/// Approximates the curve given by `evaluate` over `[0, 1]` by a polyline whose vertices lie
+/// on the curve and whose segments stay within `tolerance` of it.
+///
+/// Every interval is split in half until the curve points at a quarter, half and three quarters
+/// of it are closer than `tolerance` to the chord, so flat parts get few vertices and tight bends many.
+/// The tolerance is in the units of the curve points, e.g. pixels for curves in window coordinates.
+/// Returns an empty polyline when the curve cannot be evaluated at its start.
+pub fn flatten<F>(evaluate: F, tolerance: f32) -> Vec<(f32, f32)>
+    where F: Fn(f32) -> Option<(f32, f32)> {
+    let mut polyline = Vec::new();
+    let mut start = match evaluate(0.0) {
+        Some(point) => point,
+        None => return polyline,
+    };
+
+    polyline.push(start);
+    for segment in 0..INITIAL_SEGMENTS {
+        let (t0, t1) = (segment as f32 / INITIAL_SEGMENTS as f32, (segment + 1) as f32 / INITIAL_SEGMENTS as f32);
+        let end = match evaluate(t1) {
+            Some(point) => point,
+            None => continue,
+        };
+        subdivide(&evaluate, (t0, start), (t1, end), tolerance.max(f32::EPSILON), 0, &mut polyline);
+        start = end;
+    }
+    polyline
+}
+
+/// Appends the vertices after `start` up to and including `end`.
+fn subdivide<F>(evaluate: &F, start: (f32, (f32, f32)), end: (f32, (f32, f32)), tolerance: f32, depth: u32, polyline: &mut Vec<(f32, f32)>)
+    where F: Fn(f32) -> Option<(f32, f32)> {
+    let (t0, t1) = (start.0, end.0);
+    let middle = (0.5 * (t0 + t1), evaluate(0.5 * (t0 + t1)));
+    let is_flat = [0.25, 0.75].iter()
+        .map(|fraction| evaluate(t0 + fraction * (t1 - t0)))
+        .chain(std::iter::once(middle.1))
+        .flatten()
+        .all(|point| distance_to_segment(point, start.1, end.1) <= tolerance);
+
+    match middle {
+        (t, Some(point)) if !is_flat && depth < MAX_DEPTH => {
+            subdivide(evaluate, start, (t, point), tolerance, depth + 1, polyline);
+            subdivide(evaluate, (t, point), end, tolerance, depth + 1, polyline);
+        },
+        _ => polyline.push(end.1),
+    }
+}
+
+fn distance_to_segment(point: (f32, f32), a: (f32, f32), b: (f32, f32)) -> f32 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let length_squared = dx * dx + dy * dy;
+    let t = if length_squared > 0.0 {
+        (((point.0 - a.0) * dx + (point.1 - a.1) * dy) / length_squared).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    ((point.0 - a.0 - t * dx).powi(2) + (point.1 - a.1 - t * dy).powi(2)).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curve::bezier::BezierCurve;
+
+    #[test]
+    fn line_needs_no_subdivision() {
+        let polyline = flatten(|t| Some((2.0 * t, t)), 0.1);
+        assert_eq!(polyline.len(), INITIAL_SEGMENTS + 1);
+        assert_eq!(polyline[0], (0.0, 0.0));
+        assert_eq!(polyline[INITIAL_SEGMENTS], (2.0, 1.0));
+    }
+
+    #[test]
+    fn polyline_stays_within_tolerance() {
+        let curve = BezierCurve::new(vec![(0.0, 0.0), (100.0, 300.0), (300.0, -300.0), (400.0, 50.0)]);
+        let tolerance = 0.5;
+        let polyline = flatten(|t| curve.evaluate(t), tolerance);
+        for step in 0..=1000 {
+            let point = curve.evaluate(step as f32 / 1000.0).unwrap();
+            let distance = polyline.windows(2)
+                .map(|pair| distance_to_segment(point, pair[0], pair[1]))
+                .fold(f32::MAX, f32::min);
+            assert!(distance <= tolerance * 1.5, "distance {} at step {}", distance, step);
+        }
+    }
+
+    #[test]
+    fn tighter_tolerance_adds_vertices() {
+        let curve = BezierCurve::quarter_circle((0.0, 0.0), 500.0);
+        let coarse = flatten(|t| curve.evaluate(t), 1.0);
+        let fine = flatten(|t| curve.evaluate(t), 0.1);
+        assert!(fine.len() > coarse.len());
+        assert_eq!(fine.last(), curve.evaluate(1.0).as_ref());
+    }
+}
@@ -0,0 +1,257 @@
+use crate::curve::bezier::BezierCurve;
+use crate::curve::linalg::{self, binomial};
+use crate::curve::path::BezierPath;
+
+/// Knot spacing of a Catmull-Rom spline, the interval between two points is their distance
+/// raised to the power of `alpha`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CatmullRomParameterization {
+    /// `alpha = 0`
+    Uniform,
+    /// `alpha = 0.5`, never forms cusps or self-intersections within a segment.
+    Centripetal,
+    /// `alpha = 1`
+    Chordal,
+}
+
+impl CatmullRomParameterization {
+    pub fn alpha(&self) -> f32 {
+        match self {
+            CatmullRomParameterization::Uniform => 0.0,
+            CatmullRomParameterization::Centripetal => 0.5,
+            CatmullRomParameterization::Chordal => 1.0,
+        }
+    }
+}
+
+fn sub(a: (f32, f32), b: (f32, f32)) -> (f32, f32) {
+    (a.0 - b.0, a.1 - b.1)
+}
+
+fn scale(a: (f32, f32), factor: f32) -> (f32, f32) {
+    (a.0 * factor, a.1 * factor)
+}
+
+/// Point before the first (or after the last) one, mirroring its neighbour so that
+/// the end segments get a sensible tangent.
+fn phantom_point(end: (f32, f32), neighbour: (f32, f32)) -> (f32, f32) {
+    (2.0 * end.0 - neighbour.0, 2.0 * end.1 - neighbour.1)
+}
+
+/// Points padded with a phantom point at each end.
+fn padded(points: &[(f32, f32)]) -> Vec<(f32, f32)> {
+    let count = points.len();
+    let mut padded = Vec::with_capacity(count + 2);
+    padded.push(phantom_point(points[0], points[1]));
+    padded.extend_from_slice(points);
+    padded.push(phantom_point(points[count - 1], points[count - 2]));
+    padded
+}
+
+/// Cubic Bezier path through `points` whose segment `i` leaves point `i` with `outgoing[i]`
+/// and reaches point `i + 1` with `incoming[i + 1]`, both as derivatives over the segment.
+fn path_from_tangents(points: &[(f32, f32)], outgoing: &[(f32, f32)], incoming: &[(f32, f32)]) -> BezierPath {
+    let mut bezier_points = Vec::with_capacity(3 * points.len());
+    for idx in 0..points.len().saturating_sub(1) {
+        let (start, end) = (points[idx], points[idx + 1]);
+        bezier_points.push(start);
+        bezier_points.push((start.0 + outgoing[idx].0 / 3.0, start.1 + outgoing[idx].1 / 3.0));
+        bezier_points.push((end.0 - incoming[idx + 1].0 / 3.0, end.1 - incoming[idx + 1].1 / 3.0));
+    }
+    if points.len() > 1 {
+        bezier_points.push(points[points.len() - 1]);
+    }
+    BezierPath::new(bezier_points, 3)
+}
+
+/// Cubic Hermite spline through `points` with the given tangent at every point.
+pub fn hermite(points: &[(f32, f32)], tangents: &[(f32, f32)]) -> BezierPath {
+    assert_eq!(points.len(), tangents.len(), "every point needs a tangent");
+    path_from_tangents(points, tangents, tangents)
+}
+
+/// Catmull-Rom spline through `points` as a cubic Bezier path.
+pub fn catmull_rom(points: &[(f32, f32)], parameterization: CatmullRomParameterization) -> BezierPath {
+    if points.len() < 2 {
+        return BezierPath::new(Vec::new(), 3);
+    }
+
+    let alpha = parameterization.alpha();
+    let padded = padded(points);
+    let interval = |a: (f32, f32), b: (f32, f32)| {
+        let distance = ((b.0 - a.0).powi(2) + (b.1 - a.1).powi(2)).sqrt();
+        distance.powf(alpha).max(f32::EPSILON)
+    };
+
+    let mut outgoing = Vec::with_capacity(points.len());
+    let mut incoming = Vec::with_capacity(points.len());
+    for idx in 0..points.len() {
+        let (previous, current, next) = (padded[idx], padded[idx + 1], padded[idx + 2]);
+        let (dt0, dt1) = (interval(previous, current), interval(current, next));
+        // Derivative at `current` with respect to the knot parameter of the spline.
+        let tangent = (
+            (current.0 - previous.0) / dt0 - (next.0 - previous.0) / (dt0 + dt1) + (next.0 - current.0) / dt1,
+            (current.1 - previous.1) / dt0 - (next.1 - previous.1) / (dt0 + dt1) + (next.1 - current.1) / dt1,
+        );
+        // Every segment is reparameterized to [0, 1], which scales the derivative by its interval.
+        outgoing.push(scale(tangent, dt1));
+        incoming.push(scale(tangent, dt0));
+    }
+    path_from_tangents(points, &outgoing, &incoming)
+}
+
+/// Kochanek-Bartels spline through `points` as a cubic Bezier path. All parameters
+/// at zero give the uniform Catmull-Rom spline.
+pub fn kochanek_bartels(points: &[(f32, f32)], tension: f32, continuity: f32, bias: f32) -> BezierPath {
+    if points.len() < 2 {
+        return BezierPath::new(Vec::new(), 3);
+    }
+
+    let padded = padded(points);
+    let (t, c, b) = (tension, continuity, bias);
+    let mut outgoing = Vec::with_capacity(points.len());
+    let mut incoming = Vec::with_capacity(points.len());
+    for idx in 0..points.len() {
+        let backward = sub(padded[idx + 1], padded[idx]);
+        let forward = sub(padded[idx + 2], padded[idx + 1]);
+        let blend = |back_factor: f32, forward_factor: f32| (
+            0.5 * (1.0 - t) * (back_factor * backward.0 + forward_factor * forward.0),
+            0.5 * (1.0 - t) * (back_factor * backward.1 + forward_factor * forward.1),
+        );
+        outgoing.push(blend((1.0 + b) * (1.0 + c), (1.0 - b) * (1.0 - c)));
+        incoming.push(blend((1.0 + b) * (1.0 - c), (1.0 - b) * (1.0 + c)));
+    }
+    path_from_tangents(points, &outgoing, &incoming)
+}
+
+/// Parameters of `points` in `[0, 1]`, spaced by their distances raised to the power of the parameterization's `alpha`.
+pub fn parameters(points: &[(f32, f32)], parameterization: CatmullRomParameterization) -> Vec<f32> {
+    let alpha = parameterization.alpha();
+    let mut parameters = Vec::with_capacity(points.len());
+    let mut total = 0.0;
+    parameters.push(0.0);
+    for pair in points.windows(2) {
+        let distance = ((pair[1].0 - pair[0].0).powi(2) + (pair[1].1 - pair[0].1).powi(2)).sqrt();
+        total += distance.powf(alpha);
+        parameters.push(total);
+    }
+    parameters.iter().map(|parameter| if total > 0.0 { parameter / total } else { 0.0 }).collect()
+}
+
+/// Single Bezier curve of degree `points.len() - 1` that passes through `points` at their
+/// parameters. Its control points solve the linear system of the Bernstein polynomials at those
+/// parameters. Returns `None` for fewer than two points or when points coincide.
+///
+/// High degrees oscillate between the points, like any polynomial interpolation.
+pub fn bezier_through(points: &[(f32, f32)], parameterization: CatmullRomParameterization) -> Option<BezierCurve> {
+    if points.len() < 2 {
+        return None;
+    }
+
+    let degree = points.len() - 1;
+    let matrix = parameters(points, parameterization).iter()
+        .map(|&t| {
+            let t = t as f64;
+            (0..=degree).map(|idx| binomial(degree, idx) * t.powi(idx as i32) * (1.0 - t).powi((degree - idx) as i32)).collect()
+        })
+        .collect();
+    let rhs = points.iter().map(|point| vec![point.0 as f64, point.1 as f64]).collect();
+    let solution = linalg::solve(matrix, rhs)?;
+    Some(BezierCurve::new(solution.iter().map(|row| (row[0] as f32, row[1] as f32)).collect()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: (f32, f32), b: (f32, f32)) {
+        assert!((a.0 - b.0).abs() < 1e-4 && (a.1 - b.1).abs() < 1e-4, "{:?} != {:?}", a, b);
+    }
+
+    fn points() -> Vec<(f32, f32)> {
+        vec![(0.0, 0.0), (1.0, 2.0), (3.0, 2.5), (4.0, 0.0), (7.0, 1.0)]
+    }
+
+    #[test]
+    fn splines_pass_through_their_points() {
+        let parameterizations = [
+            CatmullRomParameterization::Uniform,
+            CatmullRomParameterization::Centripetal,
+            CatmullRomParameterization::Chordal,
+        ];
+        let mut paths: Vec<BezierPath> = parameterizations.iter().map(|p| catmull_rom(&points(), *p)).collect();
+        paths.push(kochanek_bartels(&points(), 0.3, -0.2, 0.5));
+        for path in paths {
+            assert_eq!(path.segment_count(), points().len() - 1);
+            for (idx, point) in points().iter().enumerate() {
+                assert_close(path.points()[3 * idx], *point);
+            }
+        }
+    }
+
+    #[test]
+    fn uniform_catmull_rom_uses_central_differences() {
+        let path = catmull_rom(&points(), CatmullRomParameterization::Uniform);
+        let p = points();
+        let handle = path.points()[4];
+        assert_close(handle, (p[1].0 + (p[2].0 - p[0].0) / 6.0, p[1].1 + (p[2].1 - p[0].1) / 6.0));
+    }
+
+    #[test]
+    fn neutral_kochanek_bartels_is_uniform_catmull_rom() {
+        let kb = kochanek_bartels(&points(), 0.0, 0.0, 0.0);
+        let cr = catmull_rom(&points(), CatmullRomParameterization::Uniform);
+        for (a, b) in kb.points().iter().zip(cr.points()) {
+            assert_close(*a, *b);
+        }
+    }
+
+    #[test]
+    fn full_tension_collapses_handles() {
+        let path = kochanek_bartels(&points(), 1.0, 0.0, 0.0);
+        assert_close(path.points()[1], path.points()[0]);
+        assert_close(path.points()[2], path.points()[3]);
+    }
+
+    #[test]
+    fn hermite_segments_start_with_given_tangents() {
+        let tangents = vec![(1.0, 0.0), (0.0, 3.0), (-3.0, 0.0), (0.0, -1.5), (2.0, 2.0)];
+        let path = hermite(&points(), &tangents);
+        for idx in 0..points().len() - 1 {
+            let segment = path.segment(idx);
+            let start = segment.control_points();
+            assert_close(((start[1].0 - start[0].0) * 3.0, (start[1].1 - start[0].1) * 3.0), tangents[idx]);
+            assert_close(((start[3].0 - start[2].0) * 3.0, (start[3].1 - start[2].1) * 3.0), tangents[idx + 1]);
+        }
+    }
+
+    #[test]
+    fn centripetal_spline_is_continuous_in_direction() {
+        let path = catmull_rom(&points(), CatmullRomParameterization::Centripetal);
+        let p = path.points();
+        for joint in path.joints() {
+            let incoming = sub(p[joint], p[joint - 1]);
+            let outgoing = sub(p[joint + 1], p[joint]);
+            assert!((incoming.0 * outgoing.1 - incoming.1 * outgoing.0).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn bezier_passes_through_its_points() {
+        for parameterization in [CatmullRomParameterization::Chordal, CatmullRomParameterization::Centripetal] {
+            let points = points();
+            let curve = bezier_through(&points, parameterization).unwrap();
+            assert_eq!(curve.degree(), points.len() - 1);
+            for (point, t) in points.iter().zip(parameters(&points, parameterization)) {
+                assert_close(curve.evaluate(t).unwrap(), *point);
+            }
+        }
+        assert!(bezier_through(&[(1.0, 1.0), (1.0, 1.0)], CatmullRomParameterization::Chordal).is_none());
+    }
+
+    #[test]
+    fn bezier_through_collinear_points_is_a_line() {
+        let curve = bezier_through(&[(0.0, 0.0), (1.0, 0.0), (3.0, 0.0)], CatmullRomParameterization::Chordal).unwrap();
+        assert_close(curve.control_points()[1], (1.5, 0.0));
+    }
+}
@@ -0,0 +1,170 @@
+use crate::curve::bezier::BezierCurve;
+
+/// Limit on the number of curve pairs examined, which bounds the work for overlapping curves.
+const MAX_ITERATIONS: usize = 100_000;
+
+/// Point shared by two curves, at parameter `t1` of the first and `t2` of the second.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Intersection {
+    pub t1: f32,
+    pub t2: f32,
+    pub point: (f32, f32),
+}
+
+/// Axis-aligned box around the control points, which contains the curve as long as the weights are positive.
+fn control_point_bounds(curve: &BezierCurve) -> ((f32, f32), (f32, f32)) {
+    curve.control_points().iter().fold(
+        ((f32::MAX, f32::MAX), (f32::MIN, f32::MIN)),
+        |(min, max), point| ((min.0.min(point.0), min.1.min(point.1)), (max.0.max(point.0), max.1.max(point.1))),
+    )
+}
+
+fn size(bounds: ((f32, f32), (f32, f32))) -> f32 {
+    (bounds.1.0 - bounds.0.0).max(bounds.1.1 - bounds.0.1)
+}
+
+fn overlap(a: ((f32, f32), (f32, f32)), b: ((f32, f32), (f32, f32)), tolerance: f32) -> bool {
+    a.0.0 <= b.1.0 + tolerance && b.0.0 <= a.1.0 + tolerance &&
+    a.0.1 <= b.1.1 + tolerance && b.0.1 <= a.1.1 + tolerance
+}
+
+/// Intersections of two curves by recursive subdivision.
+///
+/// Pieces of the curves whose control point boxes do not overlap are discarded, the others are
+/// halved until both boxes are smaller than `tolerance`, which is then the accuracy of the result.
+/// Neighbouring candidates are reported once, so curves that touch or overlap along a stretch
+/// give a single intersection for it.
+pub fn curve_curve(a: &BezierCurve, b: &BezierCurve, tolerance: f32) -> Vec<Intersection> {
+    let mut candidates: Vec<Intersection> = Vec::new();
+    if a.is_empty() || b.is_empty() {
+        return candidates;
+    }
+
+    let tolerance = tolerance.max(f32::EPSILON);
+    let mut stack = vec![(a.clone(), (0.0, 1.0), b.clone(), (0.0, 1.0))];
+    let mut iterations = 0;
+    while let Some((piece_a, range_a, piece_b, range_b)) = stack.pop() {
+        iterations += 1;
+        if iterations > MAX_ITERATIONS {
+            break;
+        }
+
+        let (bounds_a, bounds_b) = (control_point_bounds(&piece_a), control_point_bounds(&piece_b));
+        if !overlap(bounds_a, bounds_b, tolerance) {
+            continue;
+        }
+
+        let (size_a, size_b) = (size(bounds_a), size(bounds_b));
+        if size_a <= tolerance && size_b <= tolerance {
+            candidates.push(Intersection {
+                t1: 0.5 * (range_a.0 + range_a.1),
+                t2: 0.5 * (range_b.0 + range_b.1),
+                point: piece_a.evaluate(0.5).unwrap_or(piece_a.control_points()[0]),
+            });
+            continue;
+        }
+
+        // Halve the larger piece, so that both shrink at the same rate.
+        if size_a >= size_b {
+            let (first, second) = piece_a.split(0.5);
+            let middle = 0.5 * (range_a.0 + range_a.1);
+            stack.push((second, (middle, range_a.1), piece_b.clone(), range_b));
+            stack.push((first, (range_a.0, middle), piece_b, range_b));
+        } else {
+            let (first, second) = piece_b.split(0.5);
+            let middle = 0.5 * (range_b.0 + range_b.1);
+            stack.push((piece_a.clone(), range_a, second, (middle, range_b.1)));
+            stack.push((piece_a, range_a, first, (range_b.0, middle)));
+        }
+    }
+
+    let mut intersections: Vec<Intersection> = cluster(candidates, 3.0 * tolerance).into_iter()
+        .filter_map(|cluster| {
+            // The candidate whose points on both curves are closest represents its cluster.
+            cluster.into_iter().min_by(|first, second| {
+                gap(a, b, first).total_cmp(&gap(a, b, second))
+            })
+        })
+        .collect();
+    intersections.sort_by(|first, second| first.t1.total_cmp(&second.t1));
+    intersections
+}
+
+fn distance(a: (f32, f32), b: (f32, f32)) -> f32 {
+    ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt()
+}
+
+/// Distance between the points of both curves at the parameters of `intersection`.
+fn gap(a: &BezierCurve, b: &BezierCurve, intersection: &Intersection) -> f32 {
+    match (a.evaluate(intersection.t1), b.evaluate(intersection.t2)) {
+        (Some(first), Some(second)) => distance(first, second),
+        _ => f32::MAX,
+    }
+}
+
+/// Groups candidates that are connected by steps of at most `radius`.
+fn cluster(candidates: Vec<Intersection>, radius: f32) -> Vec<Vec<Intersection>> {
+    let mut clusters: Vec<Vec<Intersection>> = Vec::new();
+    for candidate in candidates {
+        let (joined, separate): (Vec<_>, Vec<_>) = clusters.into_iter()
+            .partition(|cluster| cluster.iter().any(|member| distance(member.point, candidate.point) <= radius));
+        let mut merged: Vec<Intersection> = joined.into_iter().flatten().collect();
+        merged.push(candidate);
+        clusters = separate;
+        clusters.push(merged);
+    }
+    clusters
+}
+
+/// Intersections of a curve with the line segment from `start` to `end`. The second parameter
+/// of every intersection is the fraction of the way along the segment.
+pub fn curve_line(curve: &BezierCurve, start: (f32, f32), end: (f32, f32), tolerance: f32) -> Vec<Intersection> {
+    curve_curve(curve, &BezierCurve::new(vec![start, end]), tolerance)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: (f32, f32), b: (f32, f32)) {
+        assert!((a.0 - b.0).abs() < 1e-2 && (a.1 - b.1).abs() < 1e-2, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn crossing_lines() {
+        let a = BezierCurve::new(vec![(0.0, 0.0), (4.0, 4.0)]);
+        let intersections = curve_line(&a, (0.0, 4.0), (4.0, 0.0), 1e-3);
+        assert_eq!(intersections.len(), 1);
+        assert!((intersections[0].t1 - 0.5).abs() < 1e-3 && (intersections[0].t2 - 0.5).abs() < 1e-3);
+        assert_close(intersections[0].point, (2.0, 2.0));
+    }
+
+    #[test]
+    fn s_curve_crosses_a_line_three_times() {
+        let curve = BezierCurve::new(vec![(0.0, 0.0), (100.0, 300.0), (200.0, -300.0), (300.0, 0.0)]);
+        let intersections = curve_line(&curve, (-10.0, 0.0), (310.0, 0.0), 1e-2);
+        assert_eq!(intersections.len(), 3);
+        for intersection in intersections {
+            assert_close(curve.evaluate(intersection.t1).unwrap(), intersection.point);
+            assert!(intersection.point.1.abs() < 1e-1);
+        }
+    }
+
+    #[test]
+    fn parameters_point_at_the_intersection_on_both_curves() {
+        let a = BezierCurve::new(vec![(0.0, 0.0), (50.0, 200.0), (150.0, 200.0), (200.0, 0.0)]);
+        let b = BezierCurve::rational(vec![(0.0, 150.0), (100.0, -100.0), (200.0, 150.0)], vec![1.0, 2.0, 1.0]);
+        let intersections = curve_curve(&a, &b, 1e-2);
+        assert_eq!(intersections.len(), 2);
+        for intersection in intersections {
+            assert_close(a.evaluate(intersection.t1).unwrap(), b.evaluate(intersection.t2).unwrap());
+        }
+    }
+
+    #[test]
+    fn separate_curves_do_not_intersect() {
+        let a = BezierCurve::new(vec![(0.0, 0.0), (1.0, 1.0), (2.0, 0.0)]);
+        let b = BezierCurve::new(vec![(0.0, 2.0), (1.0, 3.0), (2.0, 2.0)]);
+        assert!(curve_curve(&a, &b, 1e-3).is_empty());
+    }
+}
@@ -0,0 +1,75 @@
+/// Solves `matrix * x = rhs` for every column of `rhs` by Gaussian elimination with
+/// partial pivoting. `matrix` is square and stored row by row, `rhs` has one row per
+/// equation. Returns `None` when the matrix is singular.
+pub(crate) fn solve(mut matrix: Vec<Vec<f64>>, mut rhs: Vec<Vec<f64>>) -> Option<Vec<Vec<f64>>> {
+    let size = matrix.len();
+    for column in 0..size {
+        let pivot = (column..size).max_by(|a, b| matrix[*a][column].abs().total_cmp(&matrix[*b][column].abs()))?;
+        if matrix[pivot][column].abs() < 1e-12 {
+            return None;
+        }
+        matrix.swap(column, pivot);
+        rhs.swap(column, pivot);
+
+        for row in column + 1..size {
+            let factor = matrix[row][column] / matrix[column][column];
+            if factor == 0.0 {
+                continue;
+            }
+            let (upper, lower) = matrix.split_at_mut(row);
+            for (value, pivot_value) in lower[0][column..].iter_mut().zip(&upper[column][column..]) {
+                *value -= factor * pivot_value;
+            }
+            for idx in 0..rhs[row].len() {
+                rhs[row][idx] -= factor * rhs[column][idx];
+            }
+        }
+    }
+
+    for column in (0..size).rev() {
+        for idx in 0..rhs[column].len() {
+            let mut value = rhs[column][idx];
+            for row in column + 1..size {
+                value -= matrix[column][row] * rhs[row][idx];
+            }
+            rhs[column][idx] = value / matrix[column][column];
+        }
+    }
+    Some(rhs)
+}
+
+/// Binomial coefficient `n` over `k`.
+pub(crate) fn binomial(n: usize, k: usize) -> f64 {
+    (0..k).fold(1.0, |value, idx| value * (n - idx) as f64 / (idx + 1) as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn solves_small_system() {
+        let matrix = vec![vec![0.0, 2.0, 1.0], vec![1.0, 1.0, 0.0], vec![3.0, 0.0, 1.0]];
+        let rhs = vec![vec![6.0, -3.0], vec![3.0, 0.0], vec![5.0, 2.0]];
+        let solution = solve(matrix, rhs).unwrap();
+        let expected = [[1.0, 1.0], [2.0, -1.0], [2.0, -1.0]];
+        for (row, expected_row) in solution.iter().zip(expected) {
+            for (value, expected_value) in row.iter().zip(expected_row) {
+                assert!((value - expected_value).abs() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn singular_system_has_no_solution() {
+        let matrix = vec![vec![1.0, 2.0], vec![2.0, 4.0]];
+        assert!(solve(matrix, vec![vec![1.0], vec![2.0]]).is_none());
+    }
+
+    #[test]
+    fn binomial_coefficients() {
+        assert_eq!(binomial(5, 0), 1.0);
+        assert_eq!(binomial(5, 2), 10.0);
+        assert_eq!(binomial(10, 5), 252.0);
+    }
+}
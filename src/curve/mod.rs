@@ -0,0 +1,16 @@
+pub mod bezier;
+pub mod path;
+pub mod bspline;
+pub mod nurbs;
+pub mod interpolating;
+pub mod arc_length;
+pub mod flatten;
+pub mod intersection;
+pub mod analysis;
+pub mod offset;
+pub mod svg;
+pub mod fitting;
+pub mod evaluator;
+pub mod easing;
+mod linalg;
+mod roots;
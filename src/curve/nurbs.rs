@@ -0,0 +1,213 @@
+use crate::curve::bezier::BezierCurve;
+use crate::curve::bspline::{self, BSplineError};
+
+/// Non-uniform rational B-spline, i.e. a B-spline over weighted control points.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NurbsCurve {
+    control_points: Vec<(f32, f32)>,
+    weights: Vec<f32>,
+    degree: usize,
+    knots: Vec<f32>,
+}
+
+impl NurbsCurve {
+    /// Creates the curve, the knot vector has the same requirements as for a [`bspline::BSplineCurve`]
+    /// and there must be exactly one positive weight per control point.
+    pub fn new(control_points: Vec<(f32, f32)>, weights: Vec<f32>, degree: usize, knots: Vec<f32>) -> Result<Self, BSplineError> {
+        assert_eq!(control_points.len(), weights.len(), "every control point needs a weight");
+        bspline::validate_knots(control_points.len(), degree, &knots)?;
+        Ok(Self { control_points, weights, degree, knots })
+    }
+
+    pub fn control_points(&self) -> &[(f32, f32)] {
+        &self.control_points
+    }
+
+    pub fn weights(&self) -> &[f32] {
+        &self.weights
+    }
+
+    pub fn degree(&self) -> usize {
+        self.degree
+    }
+
+    pub fn knots(&self) -> &[f32] {
+        &self.knots
+    }
+
+    /// Parameter range over which the curve is defined.
+    pub fn domain(&self) -> (f32, f32) {
+        (self.knots[self.degree], self.knots[self.control_points.len()])
+    }
+
+    fn homogeneous(&self, index: usize) -> (f32, f32, f32) {
+        let (point, weight) = (self.control_points[index], self.weights[index]);
+        (point.0 * weight, point.1 * weight, weight)
+    }
+
+    /// Evaluates the curve at `u`, clamped to the domain, using de Boor's algorithm
+    /// on the homogeneous control points.
+    pub fn evaluate(&self, u: f32) -> (f32, f32) {
+        let (start, end) = self.domain();
+        let u = u.clamp(start, end);
+        let span = bspline::knot_span(&self.knots, self.degree, self.control_points.len(), u);
+        let p = self.degree;
+        let mut buffer: Vec<(f32, f32, f32)> = (span - p..=span).map(|idx| self.homogeneous(idx)).collect();
+        for r in 1..=p {
+            for j in (r..=p).rev() {
+                let i = j + span - p;
+                let denominator = self.knots[i + 1 + p - r] - self.knots[i];
+                let alpha = if denominator > 0.0 { (u - self.knots[i]) / denominator } else { 0.0 };
+                buffer[j] = homogeneous_lerp(buffer[j - 1], buffer[j], alpha);
+            }
+        }
+
+        (buffer[p].0 / buffer[p].2, buffer[p].1 / buffer[p].2)
+    }
+
+    /// Evaluates the curve at the fraction `t` of its domain.
+    pub fn evaluate_normalized(&self, t: f32) -> (f32, f32) {
+        let (start, end) = self.domain();
+        self.evaluate(start + t * (end - start))
+    }
+
+    /// Inserts the knot `u` once using Boehm's algorithm. The returned curve has one more
+    /// control point and the exact same shape. The multiplicity of `u` may not exceed the degree.
+    pub fn insert_knot(&self, u: f32) -> Result<NurbsCurve, BSplineError> {
+        let (start, end) = self.domain();
+        if u < start || u > end {
+            return Err(BSplineError::KnotOutsideDomain);
+        }
+        if bspline::knot_multiplicity(&self.knots, u) >= self.degree {
+            return Err(BSplineError::MultiplicityExceeded);
+        }
+
+        let p = self.degree;
+        let count = self.control_points.len();
+        let span = bspline::knot_span(&self.knots, p, count, u);
+        let mut control_points = Vec::with_capacity(count + 1);
+        let mut weights = Vec::with_capacity(count + 1);
+        for i in 0..=count {
+            let point = if i <= span - p {
+                self.homogeneous(i)
+            } else if i > span {
+                self.homogeneous(i - 1)
+            } else {
+                let alpha = (u - self.knots[i]) / (self.knots[i + p] - self.knots[i]);
+                homogeneous_lerp(self.homogeneous(i - 1), self.homogeneous(i), alpha)
+            };
+            control_points.push((point.0 / point.2, point.1 / point.2));
+            weights.push(point.2);
+        }
+
+        let mut knots = self.knots.clone();
+        knots.insert(span + 1, u);
+        NurbsCurve::new(control_points, weights, p, knots)
+    }
+
+    /// Rational Bezier segments that together make up the curve, each with the range of the
+    /// normalized parameter it covers. Every knot in the domain is inserted until its multiplicity
+    /// reaches the degree, after which each knot span is controlled by its own `degree + 1` points.
+    pub fn bezier_segments(&self) -> Vec<(BezierCurve, (f32, f32))> {
+        let (start, end) = self.domain();
+        let mut refined = self.clone();
+        let mut distinct: Vec<f32> = self.knots.iter().copied().filter(|knot| *knot >= start && *knot <= end).collect();
+        distinct.dedup();
+        for knot in &distinct {
+            while let Ok(curve) = refined.insert_knot(*knot) {
+                refined = curve;
+            }
+        }
+
+        let p = self.degree;
+        let count = refined.control_points.len();
+        distinct.windows(2).map(|pair| {
+            let span = bspline::knot_span(&refined.knots, p, count, pair[0]);
+            let segment = BezierCurve::rational(
+                refined.control_points[span - p..=span].to_vec(),
+                refined.weights[span - p..=span].to_vec(),
+            );
+            (segment, ((pair[0] - start) / (end - start), (pair[1] - start) / (end - start)))
+        }).collect()
+    }
+}
+
+fn homogeneous_lerp(a: (f32, f32, f32), b: (f32, f32, f32), t: f32) -> (f32, f32, f32) {
+    ((1.0 - t) * a.0 + t * b.0, (1.0 - t) * a.1 + t * b.1, (1.0 - t) * a.2 + t * b.2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curve::bspline::{knot_vector, BSplineCurve, KnotVectorKind};
+
+    fn assert_close(a: (f32, f32), b: (f32, f32)) {
+        assert!((a.0 - b.0).abs() < 1e-4 && (a.1 - b.1).abs() < 1e-4, "{:?} != {:?}", a, b);
+    }
+
+    fn curve() -> NurbsCurve {
+        let points = vec![(0.0, 0.0), (1.0, 2.0), (3.0, 3.0), (4.0, 0.0), (6.0, 1.0)];
+        let knots = knot_vector(KnotVectorKind::Clamped, points.len(), 3);
+        NurbsCurve::new(points, vec![1.0, 0.5, 2.0, 1.0, 1.5], 3, knots).unwrap()
+    }
+
+    #[test]
+    fn unit_weights_match_bspline() {
+        let points = vec![(0.0, 0.0), (1.0, 2.0), (3.0, 3.0), (4.0, 0.0), (6.0, 1.0)];
+        let knots = knot_vector(KnotVectorKind::Uniform, points.len(), 2);
+        let nurbs = NurbsCurve::new(points.clone(), vec![1.0; 5], 2, knots.clone()).unwrap();
+        let bspline = BSplineCurve::new(points, 2, knots).unwrap();
+        for step in 0..=10 {
+            let t = step as f32 / 10.0;
+            assert_close(nurbs.evaluate_normalized(t), bspline.evaluate_normalized(t));
+        }
+    }
+
+    #[test]
+    fn quadratic_nurbs_describes_a_circle_arc() {
+        let weights = vec![1.0, std::f32::consts::FRAC_1_SQRT_2, 1.0];
+        let knots = knot_vector(KnotVectorKind::Clamped, 3, 2);
+        let arc = NurbsCurve::new(vec![(1.0, 0.0), (1.0, 1.0), (0.0, 1.0)], weights, 2, knots).unwrap();
+        for step in 0..=10 {
+            let point = arc.evaluate_normalized(step as f32 / 10.0);
+            assert!(((point.0 * point.0 + point.1 * point.1).sqrt() - 1.0).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn knot_insertion_keeps_the_shape() {
+        let original = curve();
+        let refined = original.insert_knot(0.3).unwrap().insert_knot(0.3).unwrap();
+        assert_eq!(refined.control_points().len(), original.control_points().len() + 2);
+        assert_eq!(bspline::knot_multiplicity(refined.knots(), 0.3), 2);
+        for step in 0..=20 {
+            let t = step as f32 / 20.0;
+            assert_close(refined.evaluate_normalized(t), original.evaluate_normalized(t));
+        }
+    }
+
+    #[test]
+    fn bezier_segments_trace_the_curve() {
+        let points = vec![(0.0, 0.0), (1.0, 2.0), (3.0, 3.0), (4.0, 0.0), (6.0, 1.0)];
+        let knots = knot_vector(KnotVectorKind::Uniform, points.len(), 2);
+        let curves = [curve(), NurbsCurve::new(points, vec![1.0, 0.5, 2.0, 1.0, 1.5], 2, knots).unwrap()];
+        for curve in curves {
+            let segments = curve.bezier_segments();
+            assert_eq!(segments.len(), curve.control_points().len() - curve.degree());
+            for (segment, (start, end)) in segments {
+                assert_eq!(segment.degree(), curve.degree());
+                for step in 0..=4 {
+                    let s = step as f32 / 4.0;
+                    assert_close(segment.evaluate(s).unwrap(), curve.evaluate_normalized(start + s * (end - start)));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn knot_insertion_is_limited_by_degree() {
+        let refined = curve().insert_knot(0.5).unwrap().insert_knot(0.5).unwrap();
+        assert_eq!(refined.insert_knot(0.5), Err(BSplineError::MultiplicityExceeded));
+        assert_eq!(curve().insert_knot(1.5), Err(BSplineError::KnotOutsideDomain));
+    }
+}
@@ -0,0 +1,355 @@
+use crate::curve::analysis;
+use crate::curve::bezier::BezierCurve;
+use crate::curve::roots;
+
+/// Parameters at which the fitted offset is compared with the exact one.
+const ERROR_SAMPLES: usize = 8;
+/// Limit on the number of halvings of a piece, which bounds the segment count near singularities.
+const MAX_DEPTH: u32 = 10;
+/// Number of intervals searched for cusps of the offset.
+const SAMPLES: usize = 128;
+
+/// Shape of the ends of an open stroke.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineCap {
+    /// The stroke ends exactly at the end of the curve.
+    Butt,
+    /// Half a circle around the end of the curve.
+    Round,
+    /// The stroke goes on for half its width past the end of the curve.
+    Square,
+}
+
+/// Shape of the outer corner where two pieces of a stroke meet at an angle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineJoin {
+    /// The outer edges are extended until they meet, unless that point lies further than
+    /// the miter limit times half the width from the corner, in which case the join is beveled.
+    Miter,
+    /// A circular arc around the corner.
+    Round,
+    /// A straight line between the outer edges.
+    Bevel,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StrokeStyle {
+    pub width: f32,
+    pub cap: LineCap,
+    pub join: LineJoin,
+    pub miter_limit: f32,
+}
+
+fn add(a: (f32, f32), b: (f32, f32), factor: f32) -> (f32, f32) {
+    (a.0 + factor * b.0, a.1 + factor * b.1)
+}
+
+fn cross(a: (f32, f32), b: (f32, f32)) -> f32 {
+    a.0 * b.1 - a.1 * b.0
+}
+
+fn point_distance(a: (f32, f32), b: (f32, f32)) -> f32 {
+    ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt()
+}
+
+/// Unit tangent at `t`. Where the curve stops, e.g. at a cusp or at coincident end control points,
+/// the direction is taken from just inside the curve, falling back to the chord.
+fn unit_tangent(curve: &BezierCurve, t: f32) -> (f32, f32) {
+    for nudged in [t, t + 1e-3 * (0.5 - t).signum(), t + 1e-2 * (0.5 - t).signum()] {
+        if let Some([_, d1, _]) = curve.derivatives(nudged) {
+            let length = (d1.0 * d1.0 + d1.1 * d1.1).sqrt();
+            if length > 1e-6 {
+                return (d1.0 / length, d1.1 / length);
+            }
+        }
+    }
+
+    let points = curve.control_points();
+    let chord = (points[points.len() - 1].0 - points[0].0, points[points.len() - 1].1 - points[0].1);
+    let length = (chord.0 * chord.0 + chord.1 * chord.1).sqrt().max(f32::EPSILON);
+    (chord.0 / length, chord.1 / length)
+}
+
+/// Point at `distance` to the left of the curve at `t`, where left is a quarter turn
+/// counter-clockwise from the tangent.
+fn offset_point(curve: &BezierCurve, t: f32, distance: f32) -> (f32, f32) {
+    let tangent = unit_tangent(curve, t);
+    add(curve.evaluate(t).unwrap_or_default(), (-tangent.1, tangent.0), distance)
+}
+
+/// Speed of the offset curve at `t`, which is the speed of the curve scaled by `1 - distance * curvature`.
+/// It becomes negative where the offset runs backwards past a center of curvature.
+fn offset_speed(curve: &BezierCurve, t: f32, distance: f32) -> f32 {
+    match curve.derivatives(t) {
+        Some([_, d1, d2]) => {
+            let speed_squared = d1.0 * d1.0 + d1.1 * d1.1;
+            if speed_squared <= 1e-12 {
+                return 0.0;
+            }
+            speed_squared.sqrt() - distance * cross(d1, d2) / speed_squared
+        },
+        None => 0.0,
+    }
+}
+
+/// Approximation of the curve offset by `distance` to its left with cubic segments, every one within
+/// `tolerance` of the exact offset.
+///
+/// The curve is cut at its cusps, where the offset jumps to the other side, and where the offset itself
+/// has cusps because `distance` exceeds the radius of curvature. Each piece is fitted by a cubic with the
+/// exact end points and tangent directions and halved until it is close enough. The loops an offset forms
+/// behind tight bends are kept, they vanish when the outline is filled with the nonzero rule.
+pub fn offset(curve: &BezierCurve, distance: f32, tolerance: f32) -> Vec<BezierCurve> {
+    let mut segments = Vec::new();
+    if curve.degree() < 1 {
+        return segments;
+    }
+
+    let mut cuts: Vec<f32> = analysis::cusps(curve);
+    cuts.extend(roots::roots(|t| offset_speed(curve, t, distance), 0.0, 1.0, SAMPLES).into_iter().filter(|t| *t > 0.0 && *t < 1.0));
+    cuts.sort_by(f32::total_cmp);
+    cuts.push(1.0);
+    let mut start = 0.0;
+    for end in cuts {
+        if end - start > 1e-6 {
+            fit_offset(curve, (start, end), distance, tolerance.max(f32::EPSILON), 0, &mut segments);
+        }
+        start = end;
+    }
+    segments
+}
+
+fn fit_offset(curve: &BezierCurve, range: (f32, f32), distance: f32, tolerance: f32, depth: u32, segments: &mut Vec<BezierCurve>) {
+    let (start, end) = range;
+    let handle = |t: f32| (end - start) / 3.0 * offset_speed(curve, t, distance);
+    let (first, last) = (offset_point(curve, start, distance), offset_point(curve, end, distance));
+    let cubic = BezierCurve::new(vec![
+        first,
+        add(first, unit_tangent(curve, start), handle(start)),
+        add(last, unit_tangent(curve, end), -handle(end)),
+        last,
+    ]);
+
+    let error = (1..ERROR_SAMPLES)
+        .map(|step| {
+            let s = step as f32 / ERROR_SAMPLES as f32;
+            point_distance(cubic.evaluate(s).unwrap_or_default(), offset_point(curve, start + s * (end - start), distance))
+        })
+        .fold(0.0, f32::max);
+    if error <= tolerance || depth >= MAX_DEPTH {
+        segments.push(cubic);
+        return;
+    }
+
+    let middle = 0.5 * (start + end);
+    fit_offset(curve, (start, middle), distance, tolerance, depth + 1, segments);
+    fit_offset(curve, (middle, end), distance, tolerance, depth + 1, segments);
+}
+
+fn line(start: (f32, f32), end: (f32, f32)) -> BezierCurve {
+    BezierCurve::new(vec![start, end])
+}
+
+/// Circular arc around `center` starting at `from` and turning by `sweep` radians,
+/// as cubic segments of at most a quarter turn each.
+fn arc(center: (f32, f32), from: (f32, f32), sweep: f32) -> Vec<BezierCurve> {
+    let radius = point_distance(center, from);
+    let start_angle = (from.1 - center.1).atan2(from.0 - center.0);
+    let count = (sweep.abs() / std::f32::consts::FRAC_PI_2).ceil().max(1.0) as usize;
+    let step = sweep / count as f32;
+    let handle = 4.0 / 3.0 * (step / 4.0).tan() * radius;
+    (0..count).map(|idx| {
+        let (a, b) = (start_angle + idx as f32 * step, start_angle + (idx + 1) as f32 * step);
+        let (p0, p3) = (add(center, (a.cos(), a.sin()), radius), add(center, (b.cos(), b.sin()), radius));
+        BezierCurve::new(vec![p0, add(p0, (-a.sin(), a.cos()), handle), add(p3, (-b.sin(), b.cos()), -handle), p3])
+    }).collect()
+}
+
+/// Connects the left offsets of two pieces that meet at `corner`, the first arriving in the direction
+/// `incoming` and ending its offset at `from`, the second leaving in the direction `outgoing` from `to`.
+fn join(corner: (f32, f32), from: (f32, f32), incoming: (f32, f32), to: (f32, f32), outgoing: (f32, f32), style: &StrokeStyle) -> Vec<BezierCurve> {
+    if point_distance(from, to) <= 1e-4 {
+        return Vec::new();
+    }
+
+    let turn = cross(incoming, outgoing);
+    let is_reversal = turn.abs() <= 1e-6 && incoming.0 * outgoing.0 + incoming.1 * outgoing.1 < 0.0;
+    if turn > 0.0 && !is_reversal {
+        // Turning left puts the left side on the inside, where going through the corner keeps the outline filled.
+        return vec![line(from, corner), line(corner, to)];
+    }
+
+    match style.join {
+        LineJoin::Bevel => vec![line(from, to)],
+        LineJoin::Round => {
+            let (u, v) = ((from.0 - corner.0, from.1 - corner.1), (to.0 - corner.0, to.1 - corner.1));
+            // The outer side of a right turn is passed clockwise, and a reversal goes around the tip.
+            let sweep = match cross(u, v).atan2(u.0 * v.0 + u.1 * v.1) {
+                _ if is_reversal => -std::f32::consts::PI,
+                sweep if sweep > 0.0 => sweep - 2.0 * std::f32::consts::PI,
+                sweep => sweep,
+            };
+            arc(corner, from, sweep)
+        },
+        LineJoin::Miter => {
+            // Intersection of the outer edges `from + s * incoming` and `to - u * outgoing`.
+            let denominator = cross(incoming, outgoing);
+            if denominator.abs() > 1e-6 {
+                let s = cross((to.0 - from.0, to.1 - from.1), outgoing) / denominator;
+                let miter = add(from, incoming, s);
+                if point_distance(miter, corner) <= style.miter_limit * 0.5 * style.width {
+                    return vec![line(from, miter), line(miter, to)];
+                }
+            }
+            vec![line(from, to)]
+        },
+    }
+}
+
+/// Closes the outline at the end of a piece, going from its left offset `from` around `end` to its right offset.
+fn cap(end: (f32, f32), tangent: (f32, f32), from: (f32, f32), style: &StrokeStyle) -> Vec<BezierCurve> {
+    let half_width = 0.5 * style.width;
+    let to = add(end, (tangent.1, -tangent.0), half_width);
+    match style.cap {
+        LineCap::Butt => vec![line(from, to)],
+        LineCap::Square => {
+            let (corner_from, corner_to) = (add(from, tangent, half_width), add(to, tangent, half_width));
+            vec![line(from, corner_from), line(corner_from, corner_to), line(corner_to, to)]
+        },
+        LineCap::Round => arc(end, from, -std::f32::consts::PI),
+    }
+}
+
+fn reversed(curve: &BezierCurve) -> BezierCurve {
+    let control_points = curve.control_points().iter().rev().copied().collect();
+    BezierCurve::rational(control_points, curve.weights().iter().rev().copied().collect())
+}
+
+/// Left offsets of consecutive `pieces` connected by joins.
+fn stroke_side(pieces: &[BezierCurve], style: &StrokeStyle, tolerance: f32, outline: &mut Vec<BezierCurve>) {
+    let half_width = 0.5 * style.width;
+    for (idx, piece) in pieces.iter().enumerate() {
+        let offset = offset(piece, half_width, tolerance);
+        if idx > 0 {
+            let previous = &pieces[idx - 1];
+            let from = outline.last().map(|segment| segment.control_points()[segment.degree()]).unwrap_or_default();
+            let to = offset.first().map(|segment| segment.control_points()[0]).unwrap_or(from);
+            let corner = piece.control_points()[0];
+            outline.extend(join(corner, from, unit_tangent(previous, 1.0), to, unit_tangent(piece, 0.0), style));
+        }
+        outline.extend(offset);
+    }
+}
+
+/// Closed outline of the stroke of the given width along consecutive curve segments, as a chain of
+/// lines and cubic segments. The outline runs along the left side, around the end cap, back along the
+/// right side and around the start cap. It may overlap itself and is meant to be filled with the nonzero rule.
+pub fn stroke_outline(segments: &[BezierCurve], style: &StrokeStyle, tolerance: f32) -> Vec<BezierCurve> {
+    // Cusps are treated as corners, so that they get a join like the corners between segments.
+    let mut pieces: Vec<BezierCurve> = Vec::new();
+    for segment in segments.iter().filter(|segment| segment.degree() >= 1) {
+        let mut rest = segment.clone();
+        let mut consumed = 0.0;
+        for cusp in analysis::cusps(segment) {
+            let (head, tail) = rest.split((cusp - consumed) / (1.0 - consumed));
+            pieces.push(head);
+            rest = tail;
+            consumed = cusp;
+        }
+        pieces.push(rest);
+    }
+    pieces.retain(|piece| piece.control_points().iter().any(|point| point_distance(*point, piece.control_points()[0]) > 1e-6));
+    if pieces.is_empty() {
+        return Vec::new();
+    }
+
+    let mut outline = Vec::new();
+    stroke_side(&pieces, style, tolerance, &mut outline);
+    let last = &pieces[pieces.len() - 1];
+    let end_from = offset_point(last, 1.0, 0.5 * style.width);
+    outline.extend(cap(last.control_points()[last.degree()], unit_tangent(last, 1.0), end_from, style));
+
+    let backwards: Vec<BezierCurve> = pieces.iter().rev().map(reversed).collect();
+    stroke_side(&backwards, style, tolerance, &mut outline);
+    let first = &backwards[backwards.len() - 1];
+    let start_from = offset_point(first, 1.0, 0.5 * style.width);
+    outline.extend(cap(first.control_points()[first.degree()], unit_tangent(first, 1.0), start_from, style));
+    outline
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: (f32, f32), b: (f32, f32)) {
+        assert!((a.0 - b.0).abs() < 1e-2 && (a.1 - b.1).abs() < 1e-2, "{:?} != {:?}", a, b);
+    }
+
+    fn style(cap: LineCap, join: LineJoin) -> StrokeStyle {
+        StrokeStyle { width: 20.0, cap, join, miter_limit: 4.0 }
+    }
+
+    fn assert_closed_chain(outline: &[BezierCurve]) {
+        for pair in outline.windows(2) {
+            assert_close(pair[0].control_points()[pair[0].degree()], pair[1].control_points()[0]);
+        }
+        let last = &outline[outline.len() - 1];
+        assert_close(last.control_points()[last.degree()], outline[0].control_points()[0]);
+    }
+
+    #[test]
+    fn offset_of_a_circle_is_a_circle() {
+        let arc = BezierCurve::quarter_circle((0.0, 0.0), 100.0);
+        for (distance, radius) in [(10.0, 90.0), (-10.0, 110.0)] {
+            let tolerance = 0.01;
+            let segments = offset(&arc, distance, tolerance);
+            assert!(!segments.is_empty());
+            for segment in segments {
+                for step in 0..=10 {
+                    let point = segment.evaluate(step as f32 / 10.0).unwrap();
+                    let error = ((point.0 * point.0 + point.1 * point.1).sqrt() - radius).abs();
+                    assert!(error <= 2.0 * tolerance, "error {}", error);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn outline_of_a_line_with_square_caps_is_a_rectangle() {
+        let outline = stroke_outline(&[line((0.0, 0.0), (100.0, 0.0))], &style(LineCap::Square, LineJoin::Miter), 0.01);
+        assert_closed_chain(&outline);
+        let corners: Vec<(f32, f32)> = outline.iter().map(|segment| segment.control_points()[0]).collect();
+        for corner in [(-10.0, -10.0), (110.0, 10.0), (110.0, -10.0), (-10.0, 10.0)] {
+            assert!(corners.iter().any(|point| point_distance(*point, corner) < 1e-3), "missing {:?} in {:?}", corner, corners);
+        }
+    }
+
+    #[test]
+    fn round_cap_stays_at_half_width() {
+        let outline = stroke_outline(&[line((0.0, 0.0), (100.0, 0.0))], &style(LineCap::Round, LineJoin::Round), 0.01);
+        assert_closed_chain(&outline);
+        let tip = outline.iter()
+            .flat_map(|segment| (0..=10).map(move |step| segment.evaluate(step as f32 / 10.0).unwrap()))
+            .fold(f32::MIN, |right, point| right.max(point.0));
+        assert!((tip - 110.0).abs() < 1e-2);
+    }
+
+    #[test]
+    fn miter_join_meets_in_a_corner() {
+        let path = [line((0.0, 0.0), (100.0, 0.0)), line((100.0, 0.0), (100.0, -100.0))];
+        let outline = stroke_outline(&path, &style(LineCap::Butt, LineJoin::Miter), 0.01);
+        assert_closed_chain(&outline);
+        let corners: Vec<(f32, f32)> = outline.iter().map(|segment| segment.control_points()[0]).collect();
+        assert!(corners.iter().any(|point| point_distance(*point, (110.0, 10.0)) < 1e-3), "{:?}", corners);
+
+        let beveled = stroke_outline(&path, &style(LineCap::Butt, LineJoin::Bevel), 0.01);
+        assert_eq!(beveled.len(), outline.len() - 1);
+    }
+
+    #[test]
+    fn cusps_are_joined() {
+        let curve = BezierCurve::new(vec![(0.0, 0.0), (100.0, 100.0), (0.0, 100.0), (100.0, 0.0)]);
+        let outline = stroke_outline(&[curve], &style(LineCap::Butt, LineJoin::Round), 0.05);
+        assert_closed_chain(&outline);
+    }
+}
@@ -0,0 +1,315 @@
+use crate::curve::bezier::BezierCurve;
+
+/// Constraint that ties together the two segments meeting at a joint of a [`BezierPath`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Continuity {
+    /// The segments only share the anchor point.
+    Corner,
+    /// Tangents match in direction and length.
+    C1,
+    /// Tangents match in direction only.
+    G1,
+    /// Tangents and second derivatives match. Only meaningful for cubic segments,
+    /// quadratic paths treat it like `C1`.
+    C2,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum JointSide {
+    In,
+    Out,
+}
+
+/// Chain of Bezier segments of the same degree which share their end points.
+///
+/// The points are laid out as `anchor, handles.., anchor, handles.., anchor`,
+/// so segment `i` uses points `i * degree ..= (i + 1) * degree`. Trailing points
+/// that do not complete a segment are kept but not part of the path.
+/// Every point carries a continuity, which is only used for interior anchors.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BezierPath {
+    points: Vec<(f32, f32)>,
+    continuity: Vec<Continuity>,
+    segment_degree: usize,
+}
+
+impl BezierPath {
+    pub fn new(points: Vec<(f32, f32)>, segment_degree: usize) -> Self {
+        let continuity = vec![Continuity::Corner; points.len()];
+        Self::with_continuity(points, continuity, segment_degree)
+    }
+
+    pub fn with_continuity(points: Vec<(f32, f32)>, continuity: Vec<Continuity>, segment_degree: usize) -> Self {
+        assert!(segment_degree == 2 || segment_degree == 3, "only quadratic and cubic segments are supported");
+        assert_eq!(points.len(), continuity.len(), "every point needs a continuity");
+        Self { points, continuity, segment_degree }
+    }
+
+    pub fn points(&self) -> &[(f32, f32)] {
+        &self.points
+    }
+
+    pub fn continuity(&self) -> &[Continuity] {
+        &self.continuity
+    }
+
+    pub fn segment_degree(&self) -> usize {
+        self.segment_degree
+    }
+
+    pub fn segment_count(&self) -> usize {
+        self.points.len().saturating_sub(1) / self.segment_degree
+    }
+
+    pub fn segment(&self, index: usize) -> BezierCurve {
+        let start = index * self.segment_degree;
+        BezierCurve::new(self.points[start..=start + self.segment_degree].to_vec())
+    }
+
+    /// All segments, each with the range of the path parameter it covers.
+    pub fn bezier_segments(&self) -> Vec<(BezierCurve, (f32, f32))> {
+        let count = self.segment_count();
+        (0..count)
+            .map(|index| (self.segment(index), (index as f32 / count as f32, (index + 1) as f32 / count as f32)))
+            .collect()
+    }
+
+    pub fn is_anchor(&self, index: usize) -> bool {
+        index.is_multiple_of(self.segment_degree)
+    }
+
+    /// Indices of the anchors that join two segments.
+    pub fn joints(&self) -> impl Iterator<Item = usize> + '_ {
+        (1..self.segment_count()).map(move |segment| segment * self.segment_degree)
+    }
+
+    /// Evaluates the path at `t` in `[0, 1]`, every segment covers an equal share of the range.
+    pub fn evaluate(&self, t: f32) -> Option<(f32, f32)> {
+        let segment_count = self.segment_count();
+        if segment_count == 0 {
+            return None;
+        }
+
+        let scaled = t.clamp(0.0, 1.0) * segment_count as f32;
+        let segment = (scaled.floor() as usize).min(segment_count - 1);
+        self.segment(segment).evaluate(scaled - segment as f32)
+    }
+
+    /// Changes the continuity of the joint at `index` and adjusts the outgoing side to satisfy it.
+    pub fn set_continuity(&mut self, index: usize, continuity: Continuity) {
+        self.continuity[index] = continuity;
+        if self.joints().any(|joint| joint == index) {
+            self.enforce(index, JointSide::In);
+        }
+    }
+
+    /// Moves the point at `index` and updates its neighbours so that every joint keeps its continuity.
+    ///
+    /// Anchors carry their adjacent handles along. Handles drag the opposite handle of their joint,
+    /// which for `C2` joints keeps propagating along the path until it reaches a looser joint.
+    pub fn move_point(&mut self, index: usize, position: (f32, f32)) {
+        let degree = self.segment_degree;
+        let last_anchor = self.segment_count() * degree;
+        let delta = (position.0 - self.points[index].0, position.1 - self.points[index].1);
+        self.points[index] = position;
+        if index > last_anchor {
+            return;
+        }
+
+        if self.is_anchor(index) {
+            if index > 0 {
+                self.translate_point(index - 1, delta);
+            }
+            if index < last_anchor {
+                self.translate_point(index + 1, delta);
+            }
+            if degree == 2 {
+                // Quadratic handles are shared with the neighbouring joints, which have to follow them.
+                if index > 0 {
+                    self.enforce(index - degree, JointSide::Out);
+                }
+                self.enforce(index + degree, JointSide::In);
+            }
+            return;
+        }
+
+        let previous_anchor = index - index % degree;
+        let next_anchor = previous_anchor + degree;
+        if index == previous_anchor + 1 {
+            self.enforce(previous_anchor, JointSide::Out);
+        }
+        if index == next_anchor - 1 {
+            self.enforce(next_anchor, JointSide::In);
+        }
+    }
+
+    fn translate_point(&mut self, index: usize, delta: (f32, f32)) {
+        let point = &mut self.points[index];
+        *point = (point.0 + delta.0, point.1 + delta.1);
+    }
+
+    /// Adjusts the side of the joint at `anchor` opposite to `fixed` so that its continuity holds.
+    fn enforce(&mut self, mut anchor: usize, fixed: JointSide) {
+        let degree = self.segment_degree;
+        let last_anchor = self.segment_count() * degree;
+        loop {
+            if anchor == 0 || anchor >= last_anchor {
+                return;
+            }
+
+            let (fixed_handle, free_handle) = match fixed {
+                JointSide::In => (anchor - 1, anchor + 1),
+                JointSide::Out => (anchor + 1, anchor - 1),
+            };
+            let a = self.points[anchor];
+            let h = self.points[fixed_handle];
+            let continuity = self.continuity[anchor];
+            match continuity {
+                Continuity::Corner => return,
+                Continuity::C1 | Continuity::C2 => {
+                    self.points[free_handle] = (2.0 * a.0 - h.0, 2.0 * a.1 - h.1);
+                },
+                Continuity::G1 => {
+                    let free = self.points[free_handle];
+                    let free_length = ((free.0 - a.0).powi(2) + (free.1 - a.1).powi(2)).sqrt();
+                    let direction = (a.0 - h.0, a.1 - h.1);
+                    let direction_length = (direction.0 * direction.0 + direction.1 * direction.1).sqrt();
+                    if direction_length > f32::EPSILON {
+                        let scale = free_length / direction_length;
+                        self.points[free_handle] = (a.0 + direction.0 * scale, a.1 + direction.1 * scale);
+                    }
+                },
+            }
+
+            if degree == 2 {
+                // The free handle is shared with the neighbouring joint, which now has to follow.
+                anchor = match fixed {
+                    JointSide::In => anchor + degree,
+                    JointSide::Out => anchor - degree,
+                };
+                continue;
+            }
+            if continuity != Continuity::C2 {
+                return;
+            }
+
+            // Matching second derivatives moves the far handle of the free segment,
+            // which is the near handle of the next joint.
+            let (fixed_far, free_far) = match fixed {
+                JointSide::In => (anchor - 2, anchor + 2),
+                JointSide::Out => (anchor + 2, anchor - 2),
+            };
+            let (far, near, free_near) = (self.points[fixed_far], h, self.points[free_handle]);
+            self.points[free_far] = (
+                far.0 - 2.0 * near.0 + 2.0 * free_near.0,
+                far.1 - 2.0 * near.1 + 2.0 * free_near.1,
+            );
+            anchor = match fixed {
+                JointSide::In => anchor + degree,
+                JointSide::Out => anchor - degree,
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: (f32, f32), b: (f32, f32)) {
+        assert!((a.0 - b.0).abs() < 1e-4 && (a.1 - b.1).abs() < 1e-4, "{:?} != {:?}", a, b);
+    }
+
+    type Vector = (f32, f32);
+
+    /// Incoming and outgoing first and second differences at `anchor` of a cubic path.
+    fn derivatives(path: &BezierPath, anchor: usize) -> (Vector, Vector, Vector, Vector) {
+        let p = path.points();
+        let d = |i: usize, j: usize| (p[j].0 - p[i].0, p[j].1 - p[i].1);
+        let dd = |i: usize| (p[i].0 - 2.0 * p[i + 1].0 + p[i + 2].0, p[i].1 - 2.0 * p[i + 1].1 + p[i + 2].1);
+        (d(anchor - 1, anchor), d(anchor, anchor + 1), dd(anchor - 2), dd(anchor))
+    }
+
+    fn cubic_path() -> BezierPath {
+        BezierPath::new(
+            vec![(0.0, 0.0), (1.0, 1.0), (2.0, 1.0), (3.0, 0.0), (4.0, -2.0), (5.0, -1.0), (6.0, 0.0), (7.0, 1.0), (8.0, 1.0), (9.0, 0.0)],
+            3,
+        )
+    }
+
+    #[test]
+    fn segments_share_anchors() {
+        let path = cubic_path();
+        assert_eq!(path.segment_count(), 3);
+        assert_eq!(path.joints().collect::<Vec<_>>(), vec![3, 6]);
+        assert_close(path.evaluate(0.0).unwrap(), (0.0, 0.0));
+        assert_close(path.evaluate(1.0 / 3.0).unwrap(), (3.0, 0.0));
+        assert_close(path.evaluate(1.0).unwrap(), (9.0, 0.0));
+        let segments = path.bezier_segments();
+        assert_eq!(segments.len(), 3);
+        assert_close(segments[1].1, (1.0 / 3.0, 2.0 / 3.0));
+        assert_close(segments[1].0.evaluate(0.5).unwrap(), path.evaluate(0.5).unwrap());
+    }
+
+    #[test]
+    fn incomplete_path_has_no_segments() {
+        let path = BezierPath::new(vec![(0.0, 0.0), (1.0, 1.0), (2.0, 1.0)], 3);
+        assert_eq!(path.segment_count(), 0);
+        assert_eq!(path.evaluate(0.5), None);
+    }
+
+    #[test]
+    fn c1_mirrors_the_opposite_handle() {
+        let mut path = cubic_path();
+        path.set_continuity(3, Continuity::C1);
+        path.move_point(2, (2.0, 0.5));
+        let (incoming, outgoing, _, _) = derivatives(&path, 3);
+        assert_close(incoming, outgoing);
+    }
+
+    #[test]
+    fn g1_keeps_direction_and_handle_length() {
+        let mut path = cubic_path();
+        path.set_continuity(3, Continuity::G1);
+        path.move_point(4, (3.5, -3.0));
+        let (incoming, outgoing, _, _) = derivatives(&path, 3);
+        let cross = incoming.0 * outgoing.1 - incoming.1 * outgoing.0;
+        assert!(cross.abs() < 1e-4);
+        assert!(incoming.0 * outgoing.0 + incoming.1 * outgoing.1 > 0.0);
+        let incoming_length = (incoming.0 * incoming.0 + incoming.1 * incoming.1).sqrt();
+        assert!((incoming_length - (1.0f32 + 1.0).sqrt()).abs() < 1e-4);
+    }
+
+    #[test]
+    fn c2_matches_second_derivatives_along_the_path() {
+        let mut path = cubic_path();
+        path.set_continuity(3, Continuity::C2);
+        path.set_continuity(6, Continuity::C2);
+        path.move_point(2, (2.5, 2.0));
+        for joint in [3, 6] {
+            let (incoming, outgoing, curvature_in, curvature_out) = derivatives(&path, joint);
+            assert_close(incoming, outgoing);
+            assert_close(curvature_in, curvature_out);
+        }
+    }
+
+    #[test]
+    fn moving_an_anchor_carries_its_handles() {
+        let mut path = cubic_path();
+        path.set_continuity(3, Continuity::C2);
+        path.move_point(3, (3.0, 1.0));
+        assert_close(path.points()[2], (2.0, 2.0));
+        assert_close(path.points()[4], (4.0, 0.0));
+        let (incoming, outgoing, curvature_in, curvature_out) = derivatives(&path, 3);
+        assert_close(incoming, outgoing);
+        assert_close(curvature_in, curvature_out);
+    }
+
+    #[test]
+    fn quadratic_c1_propagates_through_shared_handles() {
+        let mut path = BezierPath::new(vec![(0.0, 0.0), (1.0, 1.0), (2.0, 0.0), (3.0, 1.0), (4.0, 0.0)], 2);
+        path.set_continuity(2, Continuity::C1);
+        path.move_point(1, (1.0, 2.0));
+        assert_close(path.points()[3], (3.0, -2.0));
+    }
+}
@@ -0,0 +1,76 @@
+/// Number of bisection steps, enough to reach the precision of `f32` on `[0, 1]`.
+const BISECTION_STEPS: u32 = 32;
+
+/// Roots of `f` on `[start, end]`, found as sign changes between `samples` equal intervals
+/// and refined by bisection. Roots closer together than an interval may be missed.
+pub(crate) fn roots<F>(f: F, start: f32, end: f32, samples: usize) -> Vec<f32>
+    where F: Fn(f32) -> f32 {
+    let samples = samples.max(1);
+    let at = |step: usize| start + (end - start) * step as f32 / samples as f32;
+    let mut roots = Vec::new();
+    let mut previous = (start, f(start));
+    if previous.1 == 0.0 {
+        roots.push(start);
+    }
+    for step in 1..=samples {
+        let current = (at(step), f(at(step)));
+        if current.1 == 0.0 {
+            roots.push(current.0);
+        } else if previous.1 != 0.0 && (previous.1 < 0.0) != (current.1 < 0.0) {
+            roots.push(bisect(&f, previous, current));
+        }
+        previous = current;
+    }
+    roots
+}
+
+fn bisect<F>(f: &F, mut low: (f32, f32), mut high: (f32, f32)) -> f32
+    where F: Fn(f32) -> f32 {
+    for _ in 0..BISECTION_STEPS {
+        let middle = 0.5 * (low.0 + high.0);
+        let value = f(middle);
+        if value == 0.0 {
+            return middle;
+        }
+        if (value < 0.0) == (low.1 < 0.0) {
+            low = (middle, value);
+        } else {
+            high = (middle, value);
+        }
+    }
+    0.5 * (low.0 + high.0)
+}
+
+/// Parameter in `[start, end]` at which the unimodal function `f` is smallest, by golden section search.
+pub(crate) fn minimize<F>(f: F, mut start: f32, mut end: f32) -> f32
+    where F: Fn(f32) -> f32 {
+    let ratio = 0.5 * (5.0f32.sqrt() - 1.0);
+    for _ in 0..BISECTION_STEPS {
+        let (a, b) = (end - ratio * (end - start), start + ratio * (end - start));
+        if f(a) < f(b) {
+            end = b;
+        } else {
+            start = a;
+        }
+    }
+    0.5 * (start + end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_roots_of_a_cubic() {
+        let found = roots(|t| (t - 0.2) * (t - 0.5) * (t - 0.9), 0.0, 1.0, 64);
+        assert_eq!(found.len(), 3);
+        for (root, expected) in found.iter().zip([0.2, 0.5, 0.9]) {
+            assert!((root - expected).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn finds_minimum_of_a_parabola() {
+        assert!((minimize(|t| (t - 0.3).powi(2), 0.0, 1.0) - 0.3).abs() < 1e-4);
+    }
+}
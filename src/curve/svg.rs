@@ -0,0 +1,94 @@
+use std::fmt::Write;
+
+use crate::curve::bezier::BezierCurve;
+use crate::curve::flatten::flatten;
+
+/// Distance below which the end of a segment and the start of the next one count as the same point.
+const JOIN_DISTANCE: f32 = 1e-3;
+
+fn is_same_point(a: (f32, f32), b: (f32, f32)) -> bool {
+    (a.0 - b.0).abs() <= JOIN_DISTANCE && (a.1 - b.1).abs() <= JOIN_DISTANCE
+}
+
+fn coordinates(points: &[(f32, f32)]) -> String {
+    points.iter().map(|(x, y)| format!("{} {}", x, y)).collect::<Vec<_>>().join(" ")
+}
+
+/// SVG path data for a chain of segments. Segments that do not start where the previous one ended
+/// begin a new subpath, and a subpath that returns to its start is closed.
+///
+/// Lines, quadratic and cubic segments are written exactly. Rational and higher degree segments have
+/// no SVG counterpart and are flattened into lines within `tolerance`.
+pub fn path_data(segments: &[BezierCurve], tolerance: f32) -> String {
+    let mut data = String::new();
+    let mut subpath_start: Option<(f32, f32)> = None;
+    let mut current: Option<(f32, f32)> = None;
+    for segment in segments.iter().filter(|segment| segment.degree() >= 1) {
+        let points = segment.control_points();
+        let (first, last) = (points[0], points[points.len() - 1]);
+        if !current.is_some_and(|current| is_same_point(current, first)) {
+            close(&mut data, subpath_start, current);
+            let _ = write!(data, "M {} ", coordinates(&[first]));
+            subpath_start = Some(first);
+        }
+
+        match segment.degree() {
+            _ if segment.is_rational() => {
+                let polyline = flatten(|t| segment.evaluate(t), tolerance);
+                let _ = write!(data, "L {} ", coordinates(&polyline[1..]));
+            },
+            1 => { let _ = write!(data, "L {} ", coordinates(&points[1..])); },
+            2 => { let _ = write!(data, "Q {} ", coordinates(&points[1..])); },
+            3 => { let _ = write!(data, "C {} ", coordinates(&points[1..])); },
+            _ => {
+                let polyline = flatten(|t| segment.evaluate(t), tolerance);
+                let _ = write!(data, "L {} ", coordinates(&polyline[1..]));
+            },
+        }
+        current = Some(last);
+    }
+    close(&mut data, subpath_start, current);
+    data.trim_end().to_string()
+}
+
+fn close(data: &mut String, subpath_start: Option<(f32, f32)>, current: Option<(f32, f32)>) {
+    if let (Some(start), Some(current)) = (subpath_start, current) {
+        if is_same_point(start, current) {
+            data.push_str("Z ");
+        }
+    }
+}
+
+/// Standalone SVG document of the given size holding one filled path.
+pub fn document(size: (f32, f32), path_data: &str) -> String {
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">\n  <path d=\"{}\" fill=\"black\" fill-rule=\"nonzero\"/>\n</svg>\n",
+        size.0, size.1, size.0, size.1, path_data,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn closed_chain_is_one_subpath() {
+        let segments = [
+            BezierCurve::new(vec![(0.0, 0.0), (10.0, 0.0)]),
+            BezierCurve::new(vec![(10.0, 0.0), (10.0, 5.0), (5.0, 10.0), (0.0, 10.0)]),
+            BezierCurve::new(vec![(0.0, 10.0), (-5.0, 5.0), (0.0, 0.0)]),
+        ];
+        assert_eq!(path_data(&segments, 0.1), "M 0 0 L 10 0 C 10 5 5 10 0 10 Q -5 5 0 0 Z");
+    }
+
+    #[test]
+    fn gaps_start_new_subpaths_and_rational_segments_are_flattened() {
+        let segments = [
+            BezierCurve::new(vec![(0.0, 0.0), (10.0, 0.0)]),
+            BezierCurve::quarter_circle((0.0, 0.0), 100.0),
+        ];
+        let data = path_data(&segments, 0.1);
+        assert!(data.starts_with("M 0 0 L 10 0 M 100 0 L "));
+        assert!(!data.contains('Z'));
+    }
+}